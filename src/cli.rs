@@ -44,7 +44,7 @@ pub fn print_status(engine: &mut BitBoardEngine) {
 
     if MOVELIST {
         let moves = engine.get_moves(engine.state.side_to_move);
-        print_movelist(&moves);
+        print_movelist(&moves.to_vec());
         println!("\nTotal moves: {}\n", moves.len());
     }
 
@@ -62,31 +62,24 @@ pub fn print_status(engine: &mut BitBoardEngine) {
         }
         println!();
     }
-    println!("type your next move (q to exit, perft to run perft on this position):");
+    println!(
+        "type your next move (q to exit, perft to run perft on this position, eval to show the evaluation):"
+    );
 }
 
-pub fn cli_loop(engine: &mut BitBoardEngine) {
-    // set a new game
-    let g = ChessGame::new();
-    engine.set_position(g);
+pub fn cli_loop(engine: &mut BitBoardEngine, game: ChessGame) {
+    // set the game
+    engine.set_position(game);
 
     loop {
         print_status(engine);
 
-        let moves = engine.get_moves(engine.state.side_to_move);
-
-        if moves.is_empty() {
-            let sq = engine.state.current_position[engine::bitboard::constants::WHITE_KING]
-                .get_lsb()
-                .unwrap();
-            // if the king is under attack
-            if engine.is_square_attacked_by(sq, chessire_utils::color::Color::Black) {
-                println!("Checkmate! you lost!");
-            } else {
-                println!("Stalemate!");
-            }
+        if let Some(outcome) = engine.game_outcome() {
+            print_outcome(outcome);
             return;
         }
+        let moves = engine.get_moves(engine.state.side_to_move);
+
         // interactive prompt
         let mut s = String::new();
         stdin()
@@ -104,25 +97,22 @@ pub fn cli_loop(engine: &mut BitBoardEngine) {
                 perft_details::<_>(n, engine);
                 break;
             }
+            "eval" => {
+                println!("\n{}\n", engine.get_evaluation_details());
+                // the board is printed again after the next line
+                println!("press enter to continue");
+                stdin()
+                    .read_line(&mut String::new())
+                    .expect("Did not enter a correct string");
+            }
             _ => {
                 // check if it's a valid move
                 for m in moves {
                     if m.to_string().split_whitespace().next().unwrap() == s.trim() {
                         engine.make_move(m).unwrap_or(());
 
-                        if engine.get_moves(engine.state.side_to_move).is_empty() {
-                            // find the position of the king
-                            let sq = engine.state.current_position
-                                [engine::bitboard::constants::BLACK_KING]
-                                .get_lsb()
-                                .unwrap();
-                            // if the king is under attack
-                            if engine.is_square_attacked_by(sq, chessire_utils::color::Color::White)
-                            {
-                                println!("Checkmate! you win!");
-                            } else {
-                                println!("Stalemate!");
-                            }
+                        if let Some(outcome) = engine.game_outcome() {
+                            print_outcome(outcome);
                             return;
                         }
                         engine.play_best_move();
@@ -133,3 +123,16 @@ pub fn cli_loop(engine: &mut BitBoardEngine) {
         }
     }
 }
+
+/// announces the end of the game, the player has the white pieces
+fn print_outcome(outcome: outcome::GameOutcome) {
+    use outcome::GameOutcome::*;
+    match outcome {
+        Checkmate(chessire_utils::color::Color::White) => println!("Checkmate! you win!"),
+        Checkmate(chessire_utils::color::Color::Black) => println!("Checkmate! you lost!"),
+        Stalemate => println!("Stalemate!"),
+        Repetition => println!("Draw by threefold repetition!"),
+        FiftyMoves => println!("Draw by the fifty move rule!"),
+        InsufficientMaterial => println!("Draw by insufficient material!"),
+    }
+}
@@ -1,4 +1,8 @@
 pub mod bitboard;
+pub mod limits;
+pub mod movelist;
+pub mod options;
+pub mod report;
 
 //use super::color::*;
 //use bitboard::moves::*;
@@ -10,6 +14,13 @@ use chessire_utils::moves::MoveRecord;
 use chessire_utils::moves::*;
 use chessire_utils::piece::*;
 use chessire_utils::*;
+use limits::{SearchLimits, SearchSignals};
+use movelist::MoveList;
+use options::{EngineOption, OptionValue};
+use std::sync::Arc;
+
+/// Evaluation of a position in centipawns
+pub type Score = i32;
 
 #[derive(Debug)]
 pub enum ChessEngineError {
@@ -29,25 +40,48 @@ pub trait ChessEngine {
     /// Sets the normal start position for a chess game
     fn set_start_position(&mut self);
 
+    /// Sets the start position and prepares the engine for a game unrelated to the previous one
+    fn new_game(&mut self);
+
     fn peek_piece(&self, p: Coord) -> Option<Piece>;
 
     /// returns a list of the available moves for *side*.
-    fn get_moves(&self, side: Color) -> Vec<Move>;
+    fn get_moves(&self, side: Color) -> MoveList;
 
     /// advances the state of the engines internal state according to mov.
     fn make_move(&mut self, mov: Move) -> Result<(), ()>;
 
-    /// Evaluate position
-    fn evaluate(&self) -> f32;
+    /// takes back the last move made with make_move, does nothing if there's none
+    fn unmake_move(&mut self);
+
+    /// Evaluate position, in centipawns from the point of view of white
+    fn evaluate(&self) -> Score;
+
+    /// Breakdown of the evaluation of the position by term, shown by the eval command
+    fn get_evaluation_details(&self) -> String;
 
     /// Test the legality of the given move
     fn test_move_legality(&self, mov: Move) -> Result<(), ()>;
 
-    /// Search for a best move
-    fn search_best_move(&self, depth: usize);
+    /// Search for a best move within the given limits, the result is stored in the engine
+    fn search_best_move(&mut self, limits: &SearchLimits);
+
+    /// Best move found by the last search, None if there are no legal moves
+    fn get_best_move(&self) -> Option<Move>;
+
+    /// Principal variation found by the last search
+    fn get_principal_variation(&self) -> Vec<Move>;
 
-    /// Current best move
-    fn get_best_move(&self) -> Move;
+    /// Signals used to control a search running on another thread,
+    /// they are shared between the engine and its clones
+    fn signals(&self) -> Arc<SearchSignals>;
+
+    /// Options the user can change with setoption
+    fn options(&self) -> Vec<EngineOption>;
+
+    /// Applies the value of the option *name*, the value has already been validated
+    /// but applying it can still fail, e.g. if it names a file that can't be read
+    fn set_option(&mut self, name: &str, value: OptionValue) -> Result<(), String>;
     //    /// evaluate move
     //    fn evaluate_move(&self, mov: Move) -> i32;
     //    /// get best move
@@ -72,3 +106,31 @@ pub trait ChessEngine {
     //
     fn perft_get_records(&mut self, depth: usize, moves: &Vec<String>) -> Result<Vec<MoveRecord>>;
 }
+
+/// returns the move in long algebraic notation as used by UCI (e2e4, e7e8q...)
+pub fn move_to_uci(mov: Move) -> String {
+    let mut name: String = mov
+        .to_string()
+        .split_whitespace()
+        .next()
+        .unwrap()
+        .to_string();
+    if let Some(prom) = mov.promoted_piece {
+        match prom {
+            Piece::Queen(_) => name.push('q'),
+            Piece::Rook(_) => name.push('r'),
+            Piece::Knight(_) => name.push('n'),
+            Piece::Bishop(_) => name.push('b'),
+            _ => (),
+        };
+    }
+    name
+}
+
+/// true if both moves go from the same source to the same target with the same promotion
+#[inline]
+pub fn same_move(a: Move, b: Move) -> bool {
+    a.source.to_usize() == b.source.to_usize()
+        && a.target.to_usize() == b.target.to_usize()
+        && a.promoted_piece == b.promoted_piece
+}
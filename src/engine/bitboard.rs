@@ -1,29 +1,56 @@
+pub mod activity;
 pub mod attacks;
 pub mod constants;
+pub mod evaluation;
+pub mod king_safety;
+pub mod legality;
 pub mod magics;
 pub mod moves;
 pub mod occupancy;
+pub mod ordering;
+pub mod outcome;
+pub mod params;
+pub mod pawns;
+pub mod search;
+pub mod see;
+pub mod settings;
 pub mod tests;
+pub mod trace;
+pub mod transposition;
 pub mod util;
+pub mod zobrist;
 
 use super::board::*;
 use super::color::*;
 use super::piece::*;
 use crate::castling::CastlingRights;
 use crate::engine::bitboard::moves::*;
+use crate::engine::Score;
 use anyhow::Result;
 use attacks::*;
 use chessire_utils::color::Color::{Black, White};
 use chessire_utils::moves::*;
 use constants::*;
+use evaluation::{TaperedScore, PHASE_WEIGHTS};
+use legality::*;
 use occupancy::*;
+use ordering::*;
+use params::EvalParams;
+use pawns::PawnHashTable;
+use search::*;
+use settings::*;
+use transposition::*;
 use util::*;
+use zobrist::*;
 
 // Some flags to speed up computation
+#[derive(Debug, Default, Copy, Clone)]
 pub struct PositionFlags {
     pub in_check: bool,
     pub in_double_check: bool,
     pub pins_exist: bool,
+    /// enemy pieces giving check to the side to move
+    pub checkers: BitBoard,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -35,9 +62,20 @@ pub struct BitBoardState {
     pub halfmove_clock: u32,
     pub fullmove_clock: u32,
     pub side_to_move: Color,
+    /// zobrist key of the position
+    pub hash: u64,
+    /// zobrist key of the pawns alone, for the pawn hash table
+    pub pawn_hash: u64,
+    /// material and piece square tables score from the point of view of white
+    pub psqt: TaperedScore,
+    /// game phase, from the pieces left on the board
+    pub phase: i32,
     //// optimizations
+    /// squares attacked by each color, see legality.rs
     pub squares_attacked: [BitBoard; 2],
+    /// pieces of each color pinned to their king
     pub pin_maps: [BitBoard; 2],
+    pub flags: PositionFlags,
     //// they can't never be more than 32 pieces in the board!
     pub white_piece_lists: [(Option<Piece>, usize); 16],
     pub black_piece_lists: [(Option<Piece>, usize); 16],
@@ -53,8 +91,13 @@ impl Default for BitBoardState {
             halfmove_clock: 0,
             fullmove_clock: 1,
             side_to_move: White,
+            hash: 0,
+            pawn_hash: 0,
+            psqt: TaperedScore::default(),
+            phase: 0,
             squares_attacked: [BitBoard::new(0); 2],
             pin_maps: [BitBoard::new(0); 2],
+            flags: PositionFlags::default(),
             white_piece_lists: [(None, 0); 16],
             black_piece_lists: [(None, 0); 16],
         }
@@ -101,28 +144,169 @@ impl BitBoardState {
         g.side_to_move = self.side_to_move;
         g
     }
+
+    /// places piece on sq, keeping the occupancies, the hashes and the evaluation up to date
+    #[inline]
+    pub fn add_piece(&mut self, piece: usize, sq: usize, params: &EvalParams) {
+        self.current_position[piece].set_bit(sq);
+        self.occupancies[get_bb_piece_color(piece)].set_bit(sq);
+        self.occupancies[BOTH].set_bit(sq);
+        self.hash ^= piece_key(piece, sq);
+        if piece == WHITE_PAWN || piece == BLACK_PAWN {
+            self.pawn_hash ^= piece_key(piece, sq);
+        }
+        self.psqt += params.psqt(piece, sq);
+        self.phase += PHASE_WEIGHTS[piece % 6];
+    }
+
+    /// removes piece from sq, keeping the occupancies, the hashes and the evaluation up to date
+    #[inline]
+    pub fn remove_piece(&mut self, piece: usize, sq: usize, params: &EvalParams) {
+        self.current_position[piece].reset_bit(sq);
+        self.occupancies[get_bb_piece_color(piece)].reset_bit(sq);
+        self.occupancies[BOTH].reset_bit(sq);
+        self.hash ^= piece_key(piece, sq);
+        if piece == WHITE_PAWN || piece == BLACK_PAWN {
+            self.pawn_hash ^= piece_key(piece, sq);
+        }
+        self.psqt -= params.psqt(piece, sq);
+        self.phase -= PHASE_WEIGHTS[piece % 6];
+    }
+
+    /// computes the zobrist key of the position from scratch
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (piece, bitboard) in self.current_position.iter().enumerate() {
+            for sq in *bitboard {
+                hash ^= piece_key(piece, sq);
+            }
+        }
+        if self.side_to_move == Black {
+            hash ^= ZOBRIST_KEYS.side;
+        }
+        hash ^= castling_key(self.castling_rights);
+        if let Some(sq) = self.enpassant {
+            hash ^= enpassant_key(sq);
+        }
+        hash
+    }
+
+    /// computes the zobrist key of the pawns from scratch
+    pub fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for piece in [WHITE_PAWN, BLACK_PAWN].iter().copied() {
+            for sq in self.current_position[piece] {
+                hash ^= piece_key(piece, sq);
+            }
+        }
+        hash
+    }
 }
 
-use std::rc::Rc;
+/// What make_move can't recover from the position after the move, needed to take it back
+#[derive(Debug, Clone, Copy)]
+pub struct UndoRecord {
+    /// None for a null move
+    pub mov: Option<Move>,
+    /// bitboard index of the captured piece
+    pub captured: Option<usize>,
+    pub castling_rights: CastlingRights,
+    pub enpassant: Option<usize>,
+    pub halfmove_clock: u32,
+    pub fullmove_clock: u32,
+    pub hash: u64,
+    // legality information is cheaper to copy back than to compute again
+    pub squares_attacked: [BitBoard; 2],
+    pub pin_maps: [BitBoard; 2],
+    pub flags: PositionFlags,
+}
+
+/// bitboards of the pieces that side can capture
+#[inline]
+fn enemy_pieces(side: Color) -> [usize; 6] {
+    match side {
+        White => BLACK_PIECES,
+        Black => WHITE_PIECES,
+    }
+}
+
+/// square of the pawn captured by an en passant move
+#[inline]
+fn enpassant_victim(mov: Move) -> usize {
+    match mov.piece.get_color() {
+        White => mov.target.next_down().unwrap().to_usize(),
+        Black => mov.target.next_up().unwrap().to_usize(),
+    }
+}
+
+/// rook moved when the king castles to king_target, as (bitboard, source, target)
+#[inline]
+fn castling_rook(king_target: usize) -> (usize, usize, usize) {
+    // needs a better way of setting this constants that doesn't clash with the bitboard
+    // ones!
+    let (rook, source, target) = match king_target {
+        // C1: White castling queen side
+        2 => (WHITE_ROOK, A1, D1),
+        // G1: White castling king side
+        6 => (WHITE_ROOK, H1, F1),
+        // C8
+        58 => (BLACK_ROOK, A8, D8),
+        // G8
+        62 => (BLACK_ROOK, H8, F8),
+        // should never happen!
+        _ => panic!("Castling move with wrong target square"),
+    };
+    (rook, index_from_bitmask(source), index_from_bitmask(target))
+}
+
+use std::sync::Arc;
+
+// depth used when the engine plays against the user in the cli
+const CLI_SEARCH_DEPTH: usize = 4;
 
 #[derive(Debug, Clone)]
 pub struct BitBoardEngine {
-    pub attack_tables: Rc<AttackTables>,
+    pub attack_tables: Arc<AttackTables>,
     pub state: BitBoardState,
+    pub search: SearchInfo,
+    pub settings: EngineSettings,
+    /// shared with the copies of the engine running a search
+    pub tt: Arc<TranspositionTable>,
+    /// moves made since the position was set, to take them back
+    pub history: Vec<UndoRecord>,
+    /// killers, history and countermoves, kept until a new game starts
+    pub ordering: OrderingTables,
+    /// pawn structure scores, shared with the copies of the engine running a search
+    pub pawn_table: Arc<PawnHashTable>,
+    /// material values and piece square tables of the evaluation
+    pub eval_params: Arc<EvalParams>,
 }
 
 impl Default for BitBoardEngine {
     fn default() -> Self {
         let mut engine = Self {
-            attack_tables: Rc::new(AttackTables::new()),
+            attack_tables: Arc::new(AttackTables::new()),
             state: BitBoardState::new(),
+            search: SearchInfo::default(),
+            settings: EngineSettings::default(),
+            tt: Arc::new(
+                TranspositionTable::new(DEFAULT_HASH_MB)
+                    .expect("unable to allocate the default hash table"),
+            ),
+            history: Vec::new(),
+            ordering: OrderingTables::new(),
+            pawn_table: Arc::new(PawnHashTable::new()),
+            eval_params: Arc::new(EvalParams::default()),
         };
         engine.init();
         engine
     }
 }
 
-use super::ChessEngine;
+use super::limits::{SearchLimits, SearchSignals};
+use super::movelist::MoveList;
+use super::options::{EngineOption, OptionValue};
+use super::{move_to_uci, ChessEngine};
 use chessire_utils::*;
 
 impl ChessEngine for BitBoardEngine {
@@ -133,6 +317,7 @@ impl ChessEngine for BitBoardEngine {
     }
     fn set_position(&mut self, g: ChessGame) {
         self._set_position(g.board);
+        self.history.clear();
 
         self.set_castling_rights(g.castling_rights);
         self.state.halfmove_clock = g.halfmove_clock;
@@ -144,11 +329,22 @@ impl ChessEngine for BitBoardEngine {
         } else {
             None
         };
+        self.state.hash = self.state.compute_hash();
+        self.state.pawn_hash = self.state.compute_pawn_hash();
+        self.state.psqt = self.state.compute_psqt(&self.eval_params, None);
+        self.state.phase = self.state.compute_phase();
+        self.update_legality_info();
     }
     fn set_start_position(&mut self) {
         let g = ChessGame::new();
         self.set_position(g);
     }
+    fn new_game(&mut self) {
+        self.set_start_position();
+        // results from the previous game may still be useful, but are replaced first
+        self.tt.age();
+        self.ordering.clear();
+    }
     fn peek_piece(&self, p: Coord) -> Option<Piece> {
         if self.state.occupancies[BOTH].get_bit(p.to_usize()) {
             use chessire_utils::piece::Piece::*;
@@ -180,7 +376,7 @@ impl ChessEngine for BitBoardEngine {
         }
     }
     #[inline]
-    fn get_moves(&self, side: Color) -> Vec<Move> {
+    fn get_moves(&self, side: Color) -> MoveList {
         self._get_moves(side)
     }
 
@@ -196,284 +392,167 @@ impl ChessEngine for BitBoardEngine {
 
     #[inline]
     fn test_move_legality(&self, mov: Move) -> Result<(), ()> {
-        self.clone().make_move(mov)
-    }
-
-    #[inline]
-    fn make_move(&mut self, mov: Move) -> Result<(), ()> {
-        // preserve board state
-        let backup = self.state;
-
+        // only the pieces and the occupancy after the move are needed to know if the king is safe
         let side = mov.piece.get_color();
-        let source = mov.source.to_usize();
-        let target = mov.target.to_usize();
-        let piece_index = get_bb_piece_index(mov.piece);
-
-        // move the piece
-        self.state.current_position[piece_index].reset_bit(source);
-        self.state.current_position[piece_index].set_bit(target);
+        let (pieces, occupancy) = self.board_after(mov);
 
-        // move the piece in the list
-        // if side == White {
-        //     // find the moving piece
-        //     let index = self
-        //         .state
-        //         .white_piece_lists
-        //         .iter()
-        //         .position(|&x| x == (Some(mov.piece), source))
-        //         .unwrap();
-        //     self.state.white_piece_lists[index] = (Some(mov.piece), target);
-
-        //     if let Some(index) = self
-        //         .state
-        //         .black_piece_lists
-        //         .iter()
-        //         .position(|&x| x.1 == (target))
-        //     {
-        //         self.state.black_piece_lists[index] = (None, target);
-        //     }
-        // } else {
-        //     // find the moving piece
-        //     let index = self
-        //         .state
-        //         .black_piece_lists
-        //         .iter()
-        //         .position(|&x| x == (Some(mov.piece), source))
-        //         .unwrap();
-        //     self.state.black_piece_lists[index] = (Some(mov.piece), target);
-
-        //     if let Some(index) = self
-        //         .state
-        //         .white_piece_lists
-        //         .iter()
-        //         .position(|&x| x.1 == (target))
-        //     {
-        //         self.state.white_piece_lists[index] = (None, target);
-        //     }
-        // }
-
-        // handle capture moves
-        if mov.capture {
-            for pieces in if side == White {
-                BLACK_PIECES
-            } else {
-                WHITE_PIECES
-            } {
-                self.state.current_position[pieces].reset_bit(target);
+        let (king, enemy_king) = match side {
+            White => (WHITE_KING, BLACK_KING),
+            Black => (BLACK_KING, WHITE_KING),
+        };
+        match (pieces[king].get_lsb(), pieces[enemy_king].get_lsb()) {
+            // the king of the moving side can't be left in check
+            (Some(sq), Some(_))
+                if !self.is_square_attacked_in(&pieces, occupancy, sq, side.opponent()) =>
+            {
+                Ok(())
             }
+            // and kings can't be captured
+            _ => Err(()),
         }
+    }
 
-        // promotions
-        if mov.promoted_piece.is_some() {
-            // remove the pawn from the end position
-            self.state.current_position[piece_index].reset_bit(target);
-            // and place the promoted piece
-            self.state.current_position[get_bb_piece_index(mov.promoted_piece.unwrap())]
-                .set_bit(target);
-        }
+    #[inline]
+    fn make_move(&mut self, mov: Move) -> Result<(), ()> {
+        self.test_move_legality(mov)?;
+        self.make_legal_move(mov);
+        Ok(())
+    }
 
-        // en passant captures
-        if mov.enpassant {
-            // erase enemy pawn
-            if side == White {
-                self.state.current_position[BLACK_PAWN]
-                    .reset_bit(mov.target.next_down().unwrap().to_usize());
-            } else {
-                {
-                    self.state.current_position[WHITE_PAWN]
-                        .reset_bit(mov.target.next_up().unwrap().to_usize());
-                }
+    fn unmake_move(&mut self) {
+        let undo = match self.history.pop() {
+            Some(undo) => undo,
+            None => return,
+        };
+        // null moves don't move any piece
+        if let Some(mov) = undo.mov {
+            let source = mov.source.to_usize();
+            let target = mov.target.to_usize();
+            let piece_index = get_bb_piece_index(mov.piece);
+
+            // move the piece back, a promoted piece becomes a pawn again
+            let placed = mov.promoted_piece.map_or(piece_index, get_bb_piece_index);
+            self.state.remove_piece(placed, target, &self.eval_params);
+            self.state.add_piece(piece_index, source, &self.eval_params);
+
+            if mov.castling {
+                let (rook, rook_source, rook_target) = castling_rook(target);
+                self.state
+                    .remove_piece(rook, rook_target, &self.eval_params);
+                self.state.add_piece(rook, rook_source, &self.eval_params);
             }
-            self.state.enpassant = None;
-        }
 
-        if mov.double_push {
-            // set enpassant square
-            if side == White {
-                self.state.enpassant =
-                    Some(mov.target.next_down().unwrap_or(mov.target).to_usize());
-            } else {
-                self.state.enpassant = Some(mov.target.next_up().unwrap_or(mov.target).to_usize());
+            if let Some(captured) = undo.captured {
+                let sq = if mov.enpassant {
+                    enpassant_victim(mov)
+                } else {
+                    target
+                };
+                self.state.add_piece(captured, sq, &self.eval_params);
             }
-        } else {
-            self.state.enpassant = None;
         }
 
-        // handle castling
-        if mov.castling {
-            // the king has been already moved, we just need to make sure to move the rook
-
-            // needs a better way of setting this constants that doesn't clash with the bitboard
-            // ones!
-            match mov.target.to_usize() {
-                // C1: White castling queen side
-                2 => {
-                    self.state.current_position[WHITE_ROOK].reset_bit(index_from_bitmask(A1)); // a1
-                    self.state.current_position[WHITE_ROOK].set_bit(index_from_bitmask(D1));
-                    self.state.castling_rights.white_queen_side = false;
-                }
-                // G1: White castling king side
-                6 => {
-                    self.state.current_position[WHITE_ROOK].reset_bit(index_from_bitmask(H1)); // a1
-                    self.state.current_position[WHITE_ROOK].set_bit(index_from_bitmask(F1));
-                    self.state.castling_rights.white_king_side = false;
-                }
-                // C8
-                58 => {
-                    self.state.current_position[BLACK_ROOK].reset_bit(index_from_bitmask(A8)); // a1
-                    self.state.current_position[BLACK_ROOK].set_bit(index_from_bitmask(D8));
-                    self.state.castling_rights.black_queen_side = false;
-                }
-                // G8
-                62 => {
-                    self.state.current_position[BLACK_ROOK].reset_bit(index_from_bitmask(H8)); // a1
-                    self.state.current_position[BLACK_ROOK].set_bit(index_from_bitmask(F8));
-                    self.state.castling_rights.black_king_side = false;
-                }
-                // should never happen!
-                _ => panic!("Castling move with wrong target square"),
-            }
-        }
+        self.state.castling_rights = undo.castling_rights;
+        self.state.enpassant = undo.enpassant;
+        self.state.halfmove_clock = undo.halfmove_clock;
+        self.state.fullmove_clock = undo.fullmove_clock;
+        self.state.hash = undo.hash;
+        self.state.squares_attacked = undo.squares_attacked;
+        self.state.pin_maps = undo.pin_maps;
+        self.state.flags = undo.flags;
+        self.state.side_to_move = self.state.side_to_move.opponent();
+    }
 
-        // update castling castling rights
-        for sq in [source, target] {
-            match sq {
-                // possibly moving a1 white rook
-                0 => self.state.castling_rights.white_queen_side = false,
-                // possibly moving h1 white rook
-                7 => self.state.castling_rights.white_king_side = false,
-                // possibly moving a8 black rook
-                56 => self.state.castling_rights.black_queen_side = false,
-                // possibly moving h8 black rook
-                63 => self.state.castling_rights.black_king_side = false,
-                // possibly moving white king
-                4 => {
-                    self.state.castling_rights.white_king_side = false;
-                    self.state.castling_rights.white_queen_side = false;
-                }
-                // possibly mobing black king
-                60 => {
-                    self.state.castling_rights.black_king_side = false;
-                    self.state.castling_rights.black_queen_side = false;
-                }
-                // for all other squares, do nothing
-                _ => (),
-            }
-        }
+    fn evaluate(&self) -> Score {
+        self.tapered_eval()
+    }
 
-        //update occupancies
-        self.state.occupancies[White].clear();
-        self.state.occupancies[Black].clear();
-        for i in WHITE_PIECES {
-            self.state.occupancies[White] =
-                self.state.occupancies[White] | self.state.current_position[i];
-        }
-        for i in BLACK_PIECES {
-            self.state.occupancies[Black] =
-                self.state.occupancies[Black] | self.state.current_position[i];
-        }
-        self.state.occupancies[BOTH] =
-            self.state.occupancies[White] | self.state.occupancies[Black];
+    fn get_evaluation_details(&self) -> String {
+        self.trace_eval().to_string()
+    }
 
-        self.state.side_to_move = self.state.side_to_move.opponent();
+    fn search_best_move(&mut self, limits: &SearchLimits) {
+        self.search(limits);
+    }
 
-        //check legal move
-        if let Some(white_king_sq) = self.state.current_position[WHITE_KING].get_lsb() {
-            if let Some(black_king_sq) = self.state.current_position[BLACK_KING].get_lsb() {
-                // if the king of the moving side is now exposed
-                if (side == White && self.is_square_attacked_by(white_king_sq, Black))
-                    || (side == Black && self.is_square_attacked_by(black_king_sq, White))
-                {
-                    // ilegal move, go back
-                    self.state = backup;
-                    Err(())
-                } else {
-                    // legal move
-                    Ok(())
-                }
-            } else {
-                // black king is gone! illegal move, go back
-                self.state = backup;
-                Err(())
-            }
-        } else {
-            // white king is gone! illegal move, go back
-            self.state = backup;
-            Err(())
-        }
-    }
-
-    fn evaluate(&self) -> f32 {
-        let mut position_value = 0.0;
-        // for each bitboard
-        for (i, piece) in self.state.current_position.iter().enumerate() {
-            // loop over all pieces in the current bitboard
-            let mut p = *piece;
-            while let Some(sq) = p.get_lsb() {
-                position_value += match i {
-                    // PAWN scores
-                    WHITE_PAWN => PAWN_VALUE + WHITE_PAWN_SCORES[sq] as f32,
-                    BLACK_PAWN => -PAWN_VALUE - BLACK_PAWN_SCORES[sq] as f32,
-                    // KNIGHT scores
-                    WHITE_KNIGHT => KNIGHT_VALUE + KNIGHT_SCORES[sq] as f32,
-                    BLACK_KNIGHT => -KNIGHT_VALUE - KNIGHT_SCORES[sq] as f32,
-                    // BISHOP scores
-                    WHITE_BISHOP => BISHOP_VALUE + BISHOP_SCORES[sq] as f32,
-                    BLACK_BISHOP => -BISHOP_VALUE - BISHOP_SCORES[sq] as f32,
-                    // ROOK scores
-                    WHITE_ROOK => ROOK_VALUE + ROOK_SCORES[sq] as f32,
-                    BLACK_ROOK => -ROOK_VALUE - ROOK_SCORES[sq] as f32,
-                    // QUEEN doesn't get a positional score
-                    WHITE_QUEEN => QUEEN_VALUE,
-                    BLACK_QUEEN => -QUEEN_VALUE,
-                    // KING scores
-                    WHITE_KING => KING_VALUE + KING_SCORES[sq] as f32,
-                    BLACK_KING => -KING_VALUE - KING_SCORES[sq] as f32,
-                    _ => 0.0,
-                };
-                p.reset_bit(sq);
-            }
-        }
-        position_value
+    fn get_best_move(&self) -> Option<Move> {
+        self.search.pv.first().copied()
     }
 
-    fn search_best_move(&self, _depth: usize) {
-        // time to minimax
+    fn get_principal_variation(&self) -> Vec<Move> {
+        self.search.pv.clone()
     }
 
-    fn get_best_move(&self) -> Move {
-        //placeholder
-        Move::new(
-            Coord::from_tile(index_from_bitmask(E2)),
-            Coord::from_tile(index_from_bitmask(E4)),
-            Piece::Pawn(White),
-            None,
-        )
+    fn signals(&self) -> Arc<SearchSignals> {
+        self.search.signals.clone()
     }
 
-    fn play_best_move(&mut self) {
-        let mut move_scores: Vec<(Move, f32)> = Vec::with_capacity(30);
+    fn options(&self) -> Vec<EngineOption> {
+        ENGINE_OPTIONS.to_vec()
+    }
 
-        let move_list = self.get_moves(self.state.side_to_move);
-        for m in move_list {
-            let state = self.state;
-            if self.make_move(m).is_ok() {
-                move_scores.push((m, minimax_search(self, 3)));
+    fn set_option(&mut self, name: &str, value: OptionValue) -> Result<(), String> {
+        match (name, value) {
+            (HASH, OptionValue::Spin(x)) => {
+                if x < 1 || x as usize > MAX_HASH_MB {
+                    return Err(format!(
+                        "hash size {} MB out of range [1, {}]",
+                        x, MAX_HASH_MB
+                    ));
+                }
+                // the current table is kept if the new one doesn't fit in memory
+                self.tt = Arc::new(TranspositionTable::new(x as usize)?);
+                self.settings.hash_mb = x as usize;
             }
-            self.state = state;
-        }
-
-        let mut best = move_scores[0];
-
-        for (m, s) in move_scores {
-            println!("{}\t{}", m, s);
-            // black minimizes
-            if s < best.1 {
-                best = (m, s)
+            (THREADS, OptionValue::Spin(x)) => self.settings.threads = x as usize,
+            (MULTIPV, OptionValue::Spin(x)) => self.settings.multipv = x as usize,
+            (PONDER, OptionValue::Check(x)) => self.settings.ponder = x,
+            (CLEAR_HASH, OptionValue::Button) => self.tt.clear(),
+            (MOVE_OVERHEAD, OptionValue::Spin(x)) => self.settings.move_overhead = x as u64,
+            (NULL_MOVE_PRUNING, OptionValue::Check(x)) => self.settings.null_move_pruning = x,
+            (LATE_MOVE_REDUCTIONS, OptionValue::Check(x)) => self.settings.late_move_reductions = x,
+            (REVERSE_FUTILITY_PRUNING, OptionValue::Check(x)) => {
+                self.settings.reverse_futility_pruning = x
+            }
+            (FUTILITY_PRUNING, OptionValue::Check(x)) => self.settings.futility_pruning = x,
+            (RAZORING, OptionValue::Check(x)) => self.settings.razoring = x,
+            (CHECK_EXTENSIONS, OptionValue::Check(x)) => self.settings.check_extensions = x,
+            (EVAL_FILE, OptionValue::String(path)) => {
+                let params = if path.is_empty() || path == "<empty>" {
+                    EvalParams::default()
+                } else {
+                    EvalParams::load(&path)?
+                };
+                self.set_eval_params(params);
+            }
+            (EVAL_PARAMS, OptionValue::String(text)) => {
+                let mut params = (*self.eval_params).clone();
+                params.apply(&text)?;
+                self.set_eval_params(params);
             }
+            (SAVE_EVAL_FILE, OptionValue::String(path)) => self.eval_params.save(&path)?,
+            (name, OptionValue::Spin(x)) if PIECE_VALUE_OPTIONS.contains(&name) => {
+                let index = PIECE_VALUE_OPTIONS.iter().position(|o| *o == name).unwrap();
+                let mut params = (*self.eval_params).clone();
+                let (piece, endgame) = (index / 2, index % 2 == 1);
+                let mut value = params.piece_value(piece);
+                if endgame {
+                    value.eg = x as Score;
+                } else {
+                    value.mg = x as Score;
+                }
+                params.set_piece_value(piece, value);
+                self.set_eval_params(params);
+            }
+            (name, _) => return Err(format!("unhandled option {}", name)),
         }
-        if self.make_move(best.0).is_err() {
-            println!("You won!");
+        Ok(())
+    }
+
+    fn play_best_move(&mut self) {
+        self.search_best_move(&SearchLimits::depth(CLI_SEARCH_DEPTH));
+        if let Some(mov) = self.get_best_move() {
+            self.make_move(mov).unwrap_or(());
         }
     }
 
@@ -481,27 +560,24 @@ impl ChessEngine for BitBoardEngine {
         if depth != 0 {
             // generate the move list for the current position
             let move_list = self.get_moves(self.state.side_to_move);
+            // all the moves are legal, the last ply doesn't need to be played to be counted
+            if depth == 1 && !print_moves {
+                *nodes += move_list.len() as u128;
+                return;
+            }
             for mov in move_list {
-                let move_name: String = mov
-                    .to_string()
-                    .split_whitespace()
-                    .next()
-                    .unwrap()
-                    .to_string();
-                let state = self.state;
-                if self.make_move(mov).is_ok() {
-                    let mut move_nodes = 0;
-                    if depth > 1 {
-                        self.perft(depth - 1, &mut move_nodes, false);
-                    } else {
-                        move_nodes = 1;
-                    }
-                    if print_moves {
-                        println!("{}:{}", move_name, move_nodes);
-                    }
-                    *nodes += move_nodes;
-                    self.state = state;
+                self.make_legal_move(mov);
+                let mut move_nodes = 0;
+                if depth > 1 {
+                    self.perft(depth - 1, &mut move_nodes, false);
+                } else {
+                    move_nodes = 1;
+                }
+                if print_moves {
+                    println!("{}:{}", move_to_uci(mov), move_nodes);
                 }
+                *nodes += move_nodes;
+                self.unmake_move();
             }
         } else {
             *nodes += 1;
@@ -533,38 +609,20 @@ impl ChessEngine for BitBoardEngine {
             let move_list = self.get_moves(self.state.side_to_move);
 
             for mov in move_list {
-                let mut move_name: String = mov
-                    .to_string()
-                    .split_whitespace()
-                    .next()
-                    .unwrap()
-                    .to_string();
-
-                if let Some(prom) = mov.promoted_piece {
-                    match prom {
-                        Piece::Queen(_) => move_name.push('q'),
-                        Piece::Rook(_) => move_name.push('r'),
-                        Piece::Knight(_) => move_name.push('n'),
-                        Piece::Bishop(_) => move_name.push('b'),
-                        _ => (),
-                    };
-                }
+                let move_name = move_to_uci(mov);
 
-                let state = self.state;
-
-                if self.make_move(mov).is_ok() {
-                    let mut move_nodes = 0;
-                    if depth > 1 {
-                        self.perft(depth - 1, &mut move_nodes, false);
-                    } else {
-                        move_nodes = 1;
-                    }
-                    records.push(MoveRecord {
-                        name: move_name,
-                        count: move_nodes,
-                    });
-                    self.state = state;
+                self.make_legal_move(mov);
+                let mut move_nodes = 0;
+                if depth > 1 {
+                    self.perft(depth - 1, &mut move_nodes, false);
+                } else {
+                    move_nodes = 1;
                 }
+                records.push(MoveRecord {
+                    name: move_name,
+                    count: move_nodes,
+                });
+                self.unmake_move();
             }
             Ok(records)
         } else {
@@ -575,7 +633,7 @@ impl ChessEngine for BitBoardEngine {
 
 impl BitBoardEngine {
     pub fn init(&mut self) {
-        let x = Rc::get_mut(&mut self.attack_tables).unwrap();
+        let x = Arc::get_mut(&mut self.attack_tables).unwrap();
         x.init();
     }
 
@@ -637,74 +695,428 @@ impl BitBoardEngine {
     }
     pub fn set_enpassant(&mut self, x: Option<usize>) {
         self.state.enpassant = x;
+        self.state.hash = self.state.compute_hash();
     }
 
     pub fn set_castling_rights(&mut self, cr: CastlingRights) {
         self.state.castling_rights = cr;
+        self.state.hash = self.state.compute_hash();
+    }
+
+    /// changes the evaluation parameters, the scores of the previous ones are forgotten
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.eval_params = Arc::new(params);
+        self.state.psqt = self.state.compute_psqt(&self.eval_params, None);
+        self.tt.clear();
     }
     #[inline]
     pub fn is_square_attacked_by(&self, sq: usize, col: Color) -> bool {
-        match col {
-            White => {
-                let pawn = self.state.current_position[WHITE_PAWN].get()
-                    & self.attack_tables.pawn_attacks[Black as usize][sq].get()
-                    != 0;
-
-                let knight = self.state.current_position[WHITE_KNIGHT].get()
-                    & self.attack_tables.knight_attacks[sq].get()
-                    != 0;
-
-                let bishop = self.state.current_position[WHITE_BISHOP].get()
-                    & get_bishop_attack(&self.attack_tables, sq, self.state.occupancies[BOTH])
-                        .get()
-                    != 0;
-
-                let rook = self.state.current_position[WHITE_ROOK].get()
-                    & get_rook_attack(&self.attack_tables, sq, self.state.occupancies[BOTH]).get()
-                    != 0;
-
-                let queen = self.state.current_position[WHITE_QUEEN].get()
-                    & get_queen_attack(&self.attack_tables, sq, self.state.occupancies[BOTH]).get()
-                    != 0;
-
-                let king = self.state.current_position[WHITE_KING].get()
-                    & self.attack_tables.king_attacks[sq].get()
-                    != 0;
-
-                pawn || knight || bishop || rook || queen || king
+        self.is_square_attacked_in(
+            &self.state.current_position,
+            self.state.occupancies[BOTH],
+            sq,
+            col,
+        )
+    }
+
+    /// same as is_square_attacked_by, for the given pieces and occupancy instead of the current ones
+    #[inline]
+    fn is_square_attacked_in(
+        &self,
+        pieces: &[BitBoard; 12],
+        occupancy: BitBoard,
+        sq: usize,
+        col: Color,
+    ) -> bool {
+        let (pawn, knight, bishop, rook, queen, king) = piece_indices(col);
+        let tables = &self.attack_tables;
+        // pawns attack sq if a pawn of the other color on sq would attack them
+        pieces[pawn].get() & tables.pawn_attacks[col.opponent() as usize][sq].get() != 0
+            || pieces[knight].get() & tables.knight_attacks[sq].get() != 0
+            || (pieces[bishop].get() | pieces[queen].get())
+                & get_bishop_attack(tables, sq, occupancy).get()
+                != 0
+            || (pieces[rook].get() | pieces[queen].get())
+                & get_rook_attack(tables, sq, occupancy).get()
+                != 0
+            || pieces[king].get() & tables.king_attacks[sq].get() != 0
+    }
+
+    /// pieces and occupancy after mov, without making it
+    fn board_after(&self, mov: Move) -> ([BitBoard; 12], BitBoard) {
+        let side = mov.piece.get_color();
+        let source = mov.source.to_usize();
+        let target = mov.target.to_usize();
+        let piece_index = get_bb_piece_index(mov.piece);
+        let mut pieces = self.state.current_position;
+        let mut occupancy = self.state.occupancies[BOTH];
+
+        pieces[piece_index].reset_bit(source);
+        occupancy.reset_bit(source);
+        // captured pieces stop attacking
+        for i in enemy_pieces(side) {
+            pieces[i].reset_bit(target);
+        }
+        pieces[mov.promoted_piece.map_or(piece_index, get_bb_piece_index)].set_bit(target);
+        occupancy.set_bit(target);
+
+        if mov.enpassant {
+            let sq = enpassant_victim(mov);
+            pieces[get_bb_piece_index(Piece::Pawn(side.opponent()))].reset_bit(sq);
+            occupancy.reset_bit(sq);
+        }
+        if mov.castling {
+            let (rook, rook_source, rook_target) = castling_rook(target);
+            pieces[rook].reset_bit(rook_source);
+            pieces[rook].set_bit(rook_target);
+            occupancy.reset_bit(rook_source);
+            occupancy.set_bit(rook_target);
+        }
+        (pieces, occupancy)
+    }
+
+    /// true if mov attacks the enemy king, directly or by uncovering a slider
+    pub fn gives_check(&self, mov: Move) -> bool {
+        let side = mov.piece.get_color();
+        let (.., enemy_king) = piece_indices(side.opponent());
+        let (pieces, occupancy) = self.board_after(mov);
+        match pieces[enemy_king].get_lsb() {
+            Some(sq) => self.is_square_attacked_in(&pieces, occupancy, sq, side),
+            None => false,
+        }
+    }
+
+    /// bitboard index of the piece captured by mov, if any
+    pub fn captured_piece(&self, mov: Move) -> Option<usize> {
+        let side = mov.piece.get_color();
+        if mov.enpassant {
+            Some(get_bb_piece_index(Piece::Pawn(side.opponent())))
+        } else if mov.capture {
+            let target = mov.target.to_usize();
+            enemy_pieces(side)
+                .iter()
+                .copied()
+                .find(|&i| self.state.current_position[i].get_bit(target))
+        } else {
+            None
+        }
+    }
+
+    /// makes a move known to be legal, like the ones returned by get_moves, without testing it
+    pub fn make_legal_move(&mut self, mov: Move) {
+        let side = mov.piece.get_color();
+        let source = mov.source.to_usize();
+        let target = mov.target.to_usize();
+        let piece_index = get_bb_piece_index(mov.piece);
+
+        // everything needed to take the move back
+        let mut undo = UndoRecord {
+            mov: Some(mov),
+            captured: None,
+            castling_rights: self.state.castling_rights,
+            enpassant: self.state.enpassant,
+            halfmove_clock: self.state.halfmove_clock,
+            fullmove_clock: self.state.fullmove_clock,
+            hash: self.state.hash,
+            squares_attacked: self.state.squares_attacked,
+            pin_maps: self.state.pin_maps,
+            flags: self.state.flags,
+        };
+
+        // remove the old castling rights and en passant square from the hash,
+        // the new ones are added back at the end
+        self.state.hash ^= castling_key(self.state.castling_rights);
+        if let Some(sq) = self.state.enpassant {
+            self.state.hash ^= enpassant_key(sq);
+        }
+
+        // handle capture moves
+        undo.captured = self.captured_piece(mov);
+        if let Some(captured) = undo.captured {
+            let sq = if mov.enpassant {
+                enpassant_victim(mov)
+            } else {
+                target
+            };
+            self.state.remove_piece(captured, sq, &self.eval_params);
+        }
+
+        // move the piece, promotions place the promoted piece instead of the pawn
+        let placed = mov.promoted_piece.map_or(piece_index, get_bb_piece_index);
+        self.state
+            .remove_piece(piece_index, source, &self.eval_params);
+        self.state.add_piece(placed, target, &self.eval_params);
+
+        // move the piece in the list
+        // if side == White {
+        //     // find the moving piece
+        //     let index = self
+        //         .state
+        //         .white_piece_lists
+        //         .iter()
+        //         .position(|&x| x == (Some(mov.piece), source))
+        //         .unwrap();
+        //     self.state.white_piece_lists[index] = (Some(mov.piece), target);
+
+        //     if let Some(index) = self
+        //         .state
+        //         .black_piece_lists
+        //         .iter()
+        //         .position(|&x| x.1 == (target))
+        //     {
+        //         self.state.black_piece_lists[index] = (None, target);
+        //     }
+        // } else {
+        //     // find the moving piece
+        //     let index = self
+        //         .state
+        //         .black_piece_lists
+        //         .iter()
+        //         .position(|&x| x == (Some(mov.piece), source))
+        //         .unwrap();
+        //     self.state.black_piece_lists[index] = (Some(mov.piece), target);
+
+        //     if let Some(index) = self
+        //         .state
+        //         .white_piece_lists
+        //         .iter()
+        //         .position(|&x| x.1 == (target))
+        //     {
+        //         self.state.white_piece_lists[index] = (None, target);
+        //     }
+        // }
+
+        if mov.double_push {
+            // set enpassant square
+            if side == White {
+                self.state.enpassant =
+                    Some(mov.target.next_down().unwrap_or(mov.target).to_usize());
+            } else {
+                self.state.enpassant = Some(mov.target.next_up().unwrap_or(mov.target).to_usize());
             }
-            Black => {
-                let pawn = self.state.current_position[BLACK_PAWN].get()
-                    & self.attack_tables.pawn_attacks[White as usize][sq].get()
-                    != 0;
-
-                let knight = self.state.current_position[BLACK_KNIGHT].get()
-                    & self.attack_tables.knight_attacks[sq].get()
-                    != 0;
-
-                let bishop = self.state.current_position[BLACK_BISHOP].get()
-                    & get_bishop_attack(&self.attack_tables, sq, self.state.occupancies[BOTH])
-                        .get()
-                    != 0;
-
-                let rook = self.state.current_position[BLACK_ROOK].get()
-                    & get_rook_attack(&self.attack_tables, sq, self.state.occupancies[BOTH]).get()
-                    != 0;
-
-                let queen = self.state.current_position[BLACK_QUEEN].get()
-                    & get_queen_attack(&self.attack_tables, sq, self.state.occupancies[BOTH]).get()
-                    != 0;
-                let king = self.state.current_position[BLACK_KING].get()
-                    & self.attack_tables.king_attacks[sq].get()
-                    != 0;
-
-                pawn || knight || bishop || rook || queen || king
+        } else {
+            self.state.enpassant = None;
+        }
+
+        // handle castling
+        if mov.castling {
+            // the king has been already moved, we just need to make sure to move the rook
+            let (rook, rook_source, rook_target) = castling_rook(target);
+            self.state
+                .remove_piece(rook, rook_source, &self.eval_params);
+            self.state.add_piece(rook, rook_target, &self.eval_params);
+        }
+
+        // update castling castling rights
+        for sq in [source, target] {
+            match sq {
+                // possibly moving a1 white rook
+                0 => self.state.castling_rights.white_queen_side = false,
+                // possibly moving h1 white rook
+                7 => self.state.castling_rights.white_king_side = false,
+                // possibly moving a8 black rook
+                56 => self.state.castling_rights.black_queen_side = false,
+                // possibly moving h8 black rook
+                63 => self.state.castling_rights.black_king_side = false,
+                // possibly moving white king
+                4 => {
+                    self.state.castling_rights.white_king_side = false;
+                    self.state.castling_rights.white_queen_side = false;
+                }
+                // possibly mobing black king
+                60 => {
+                    self.state.castling_rights.black_king_side = false;
+                    self.state.castling_rights.black_queen_side = false;
+                }
+                // for all other squares, do nothing
+                _ => (),
             }
         }
+
+        // the fifty move counter is reset by pawn moves and captures
+        if piece_index == WHITE_PAWN || piece_index == BLACK_PAWN || undo.captured.is_some() {
+            self.state.halfmove_clock = 0;
+        } else {
+            self.state.halfmove_clock += 1;
+        }
+        if side == Black {
+            self.state.fullmove_clock += 1;
+        }
+
+        self.state.side_to_move = self.state.side_to_move.opponent();
+
+        // add the new castling rights, en passant square and side to the hash
+        self.state.hash ^= castling_key(self.state.castling_rights) ^ ZOBRIST_KEYS.side;
+        if let Some(sq) = self.state.enpassant {
+            self.state.hash ^= enpassant_key(sq);
+        }
+        debug_assert_eq!(
+            self.state.hash,
+            self.state.compute_hash(),
+            "incremental zobrist key mismatch after {}",
+            move_to_uci(mov)
+        );
+        debug_assert_eq!(
+            self.state.pawn_hash,
+            self.state.compute_pawn_hash(),
+            "incremental pawn key mismatch after {}",
+            move_to_uci(mov)
+        );
+        debug_assert_eq!(
+            (self.state.psqt, self.state.phase),
+            (
+                self.state.compute_psqt(&self.eval_params, None),
+                self.state.compute_phase()
+            ),
+            "incremental evaluation mismatch after {}",
+            move_to_uci(mov)
+        );
+
+        self.update_legality_info();
+        self.history.push(undo);
+    }
+
+    /// passes the turn to the opponent without moving, as used by null move pruning.
+    /// Taken back with unmake_move, it can't be made while in check
+    pub fn make_null_move(&mut self) {
+        let undo = UndoRecord {
+            mov: None,
+            captured: None,
+            castling_rights: self.state.castling_rights,
+            enpassant: self.state.enpassant,
+            halfmove_clock: self.state.halfmove_clock,
+            fullmove_clock: self.state.fullmove_clock,
+            hash: self.state.hash,
+            squares_attacked: self.state.squares_attacked,
+            pin_maps: self.state.pin_maps,
+            flags: self.state.flags,
+        };
+
+        // the en passant capture is only available right after the double push
+        if let Some(sq) = self.state.enpassant.take() {
+            self.state.hash ^= enpassant_key(sq);
+        }
+        self.state.halfmove_clock += 1;
+        if self.state.side_to_move == Black {
+            self.state.fullmove_clock += 1;
+        }
+        self.state.side_to_move = self.state.side_to_move.opponent();
+        self.state.hash ^= ZOBRIST_KEYS.side;
+        debug_assert_eq!(self.state.hash, self.state.compute_hash());
+
+        self.update_legality_info();
+        self.history.push(undo);
     }
+
     #[inline]
-    fn _get_moves(&self, side: Color) -> Vec<Move> {
-        let mut moves = Vec::with_capacity(30);
+    fn _get_moves(&self, side: Color) -> MoveList {
+        // the legality information is only available for the side to move
+        if side == self.state.side_to_move {
+            self.get_legal_moves()
+        } else {
+            self.get_moves_by_trial(side)
+        }
+    }
+
+    /// legal moves for the side to move, using the pins, checks and attacked squares of the position
+    pub fn get_legal_moves(&self) -> MoveList {
+        self.generate_moves(GenType::All)
+    }
+
+    /// captures, en passant and promotions, as searched by the quiescence search
+    pub fn get_captures(&self) -> MoveList {
+        self.generate_moves(GenType::Captures)
+    }
+
+    /// every legal move not returned by get_captures, castling included
+    pub fn get_quiets(&self) -> MoveList {
+        self.generate_moves(GenType::Quiets)
+    }
+
+    /// moves that get the side to move out of check, empty if it isn't in check
+    pub fn get_evasions(&self) -> MoveList {
+        if self.state.flags.in_check {
+            self.generate_moves(GenType::All)
+        } else {
+            MoveList::new()
+        }
+    }
+
+    /// quiet moves that give check to the enemy king
+    pub fn get_quiet_checks(&self) -> MoveList {
+        let mut moves = self.generate_moves(GenType::Quiets);
+        moves.retain(|m| self.gives_check(m));
+        moves
+    }
+
+    /// legal moves of the piece of the side to move on sq, used to check that a move
+    /// remembered from another position can be played in this one
+    pub fn get_moves_from(&self, sq: usize) -> MoveList {
+        self.generate_moves_from(GenType::All, BitBoard::new(1 << sq))
+    }
+
+    /// legal moves of the given type for the side to move
+    #[inline]
+    fn generate_moves(&self, gen: GenType) -> MoveList {
+        self.generate_moves_from(gen, BitBoard::new(u64::MAX))
+    }
+
+    /// legal moves of the given type for the pieces of the side to move on the sources squares
+    fn generate_moves_from(&self, gen: GenType, sources: BitBoard) -> MoveList {
+        let side = self.state.side_to_move;
+        let (.., king) = piece_indices(side);
+        let mut moves = MoveList::new();
+
+        let king_sq = match self.state.current_position[king].get_lsb() {
+            Some(sq) => sq,
+            None => return moves,
+        };
+        let targets = match gen {
+            GenType::All => BitBoard::new(u64::MAX),
+            GenType::Captures => self.state.occupancies[side.opponent()],
+            GenType::Quiets => !self.state.occupancies[BOTH],
+        };
+
+        if sources.get_bit(king_sq) {
+            get_king_moves(
+                self,
+                king_sq,
+                side,
+                targets & !self.state.squares_attacked[side.opponent() as usize],
+                &mut moves,
+            );
+        }
+        // only the king can escape a double check
+        if self.state.flags.in_double_check {
+            return moves;
+        }
+
+        let evasion_mask = self.get_evasion_mask();
+        let pinned = self.state.pin_maps[side as usize];
+        let piece_lists = match side {
+            White => WHITE_PIECES,
+            Black => BLACK_PIECES,
+        };
+        for pieces in piece_lists {
+            if pieces == king {
+                continue;
+            }
+            for source_square in self.state.current_position[pieces] & sources {
+                let mask = if pinned.get_bit(source_square) {
+                    evasion_mask & get_line_through(&self.attack_tables, king_sq, source_square)
+                } else {
+                    evasion_mask
+                };
+                self.get_piece_moves(pieces, source_square, side, mask, gen, &mut moves);
+            }
+        }
+        moves
+    }
+
+    /// pseudo legal moves of side, tried one by one on the board
+    /// slower than get_legal_moves, but works for both sides
+    pub fn get_moves_by_trial(&self, side: Color) -> MoveList {
+        let mut moves = MoveList::new();
 
         let piece_lists = match side {
             White => WHITE_PIECES,
@@ -712,28 +1124,46 @@ impl BitBoardEngine {
         };
 
         for pieces in piece_lists {
-            let mut positions = self.state.current_position[pieces];
-            while positions.get() != 0 {
-                if let Some(source_square) = positions.get_lsb() {
-                    let mut m = match pieces {
-                        WHITE_PAWN | BLACK_PAWN => get_pawn_moves(self, source_square, side),
-                        WHITE_KNIGHT | BLACK_KNIGHT => get_knight_moves(self, source_square, side),
-                        WHITE_BISHOP | BLACK_BISHOP => get_bishop_moves(self, source_square, side),
-                        WHITE_ROOK | BLACK_ROOK => get_rook_moves(self, source_square, side),
-                        WHITE_QUEEN | BLACK_QUEEN => get_queen_moves(self, source_square, side),
-                        WHITE_KING | BLACK_KING => get_king_moves(self, source_square, side),
-                        _ => vec![],
-                    };
-                    moves.append(&mut m);
-                    positions.reset_bit(source_square);
-                }
+            for source_square in self.state.current_position[pieces] {
+                let mask = BitBoard::new(u64::MAX);
+                self.get_piece_moves(pieces, source_square, side, mask, GenType::All, &mut moves);
             }
         }
 
+        moves.retain(|m| self.test_move_legality(m).is_ok());
         moves
-            .into_iter()
-            .filter(|m| self.test_move_legality(*m).is_ok())
-            .collect()
+    }
+
+    /// moves of the piece on source_square, the pawns need the generation type to split
+    /// promotions and en passant from the pushes, the other pieces only need the target squares
+    #[inline]
+    fn get_piece_moves(
+        &self,
+        pieces: usize,
+        source_square: usize,
+        side: Color,
+        mask: BitBoard,
+        gen: GenType,
+        moves: &mut MoveList,
+    ) {
+        let targets = match gen {
+            GenType::All => mask,
+            GenType::Captures => mask & self.state.occupancies[side.opponent()],
+            GenType::Quiets => mask & !self.state.occupancies[BOTH],
+        };
+        match pieces {
+            WHITE_PAWN | BLACK_PAWN => get_pawn_moves(self, source_square, side, mask, gen, moves),
+            WHITE_KNIGHT | BLACK_KNIGHT => {
+                get_knight_moves(self, source_square, side, targets, moves)
+            }
+            WHITE_BISHOP | BLACK_BISHOP => {
+                get_bishop_moves(self, source_square, side, targets, moves)
+            }
+            WHITE_ROOK | BLACK_ROOK => get_rook_moves(self, source_square, side, targets, moves),
+            WHITE_QUEEN | BLACK_QUEEN => get_queen_moves(self, source_square, side, targets, moves),
+            WHITE_KING | BLACK_KING => get_king_moves(self, source_square, side, targets, moves),
+            _ => (),
+        }
     }
 }
 
@@ -873,40 +1303,3 @@ fn initialise_pawn_attacks(table: &mut Vec<Vec<BitBoard>>) {
         table[Black as usize][i] = generate_pawn_mask(i, Black);
     }
 }
-
-fn minimax_search(engine: &mut BitBoardEngine, depth: usize) -> f32 {
-    if depth == 0 {
-        return engine.evaluate();
-    }
-    let player = engine.state.side_to_move;
-    if player == White {
-        let mut max_eval: f32 = std::f32::MIN;
-        // generate the move list for the current position
-        let move_list = engine.get_moves(player);
-        for mov in move_list {
-            // store current state
-            let state = engine.state;
-            //make move and evaluate
-            if engine.make_move(mov).is_ok() {
-                max_eval = f32::max(minimax_search(engine, depth - 1), max_eval);
-            }
-            //restore state
-            engine.state = state;
-        }
-        max_eval
-    } else {
-        let mut min_eval: f32 = std::f32::MAX;
-        // generate the move list for the current position
-        let move_list = engine.get_moves(player);
-
-        for mov in move_list {
-            let state = engine.state;
-
-            if engine.make_move(mov).is_ok() {
-                min_eval = f32::min(minimax_search(engine, depth - 1), min_eval);
-            }
-            engine.state = state;
-        }
-        min_eval
-    }
-}
@@ -0,0 +1,139 @@
+use super::attacks::*;
+use super::constants::*;
+use super::evaluation::{s, TaperedScore};
+use super::king_safety::king_zone;
+use super::legality::piece_indices;
+use super::pawns::{pawn_attack_span, relative_rank, FILE_MASKS, RANK_MASKS};
+use super::trace::{EvalTerm, EvalTrace};
+use super::BitBoardEngine;
+use crate::engine::Score;
+use chessire_utils::color::Color::{self, Black, White};
+
+/* Piece activity, see the chessprogramming wiki for details.
+ *  - mobility, the squares each piece attacks that aren't taken by a piece of its color
+ *    nor attacked by an enemy pawn, counted from the average for the piece
+ *  - attacks on the zone around the enemy king, which only become dangerous when several
+ *    pieces join them
+ *  - the bishop pair
+ *  - rooks on files without pawns of their color, and on the seventh rank
+ *  - knight outposts, defended by a pawn and out of reach of the enemy pawns
+ * Weights are indexed by piece, and score for each safe square or attacked square.
+ */
+
+#[rustfmt::skip]
+const MOBILITY: [TaperedScore; 6] = [
+    s(0, 0), s(4, 4), s(5, 5), s(2, 4), s(1, 2), s(0, 0),
+];
+/// average number of safe squares of each piece
+const MOBILITY_AVERAGE: [i32; 6] = [0, 4, 6, 7, 13, 0];
+
+#[rustfmt::skip]
+const KING_ZONE_ATTACK: [TaperedScore; 6] = [
+    s(0, 0), s(10, 2), s(10, 2), s(15, 3), s(20, 5), s(0, 0),
+];
+/// percentage of the king zone attacks scored by number of attackers
+const KING_ATTACKERS_SCALE: [Score; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+
+const BISHOP_PAIR: TaperedScore = s(30, 50);
+const KNIGHT_OUTPOST: TaperedScore = s(25, 15);
+const ROOK_OPEN_FILE: TaperedScore = s(25, 10);
+const ROOK_SEMI_OPEN_FILE: TaperedScore = s(12, 6);
+const ROOK_ON_SEVENTH: TaperedScore = s(10, 25);
+
+impl BitBoardEngine {
+    /// piece activity score, from the point of view of white
+    pub fn piece_activity(&self) -> TaperedScore {
+        self.piece_activity_of(White, None) - self.piece_activity_of(Black, None)
+    }
+
+    /// piece activity score of the pieces of color, recording each term in trace if given
+    pub fn piece_activity_of(&self, color: Color, trace: Option<&mut EvalTrace>) -> TaperedScore {
+        let enemy = color.opponent();
+        let (pawn, knight, bishop, rook, queen, _) = piece_indices(color);
+        let (enemy_pawn, _, _, _, _, enemy_king) = piece_indices(enemy);
+        let pieces = &self.state.current_position;
+        let tables = &*self.attack_tables;
+        let occupancy = self.state.occupancies[BOTH];
+        let own_pawns = pieces[pawn].get();
+        let enemy_pawns = pieces[enemy_pawn].get();
+
+        let mut enemy_pawn_attacks = 0;
+        for sq in pieces[enemy_pawn] {
+            enemy_pawn_attacks |= tables.pawn_attacks[enemy as usize][sq].get();
+        }
+        let safe = !self.state.occupancies[color as usize].get() & !enemy_pawn_attacks;
+        let zone = pieces[enemy_king]
+            .get_lsb()
+            .map_or(0, |sq| king_zone(tables, sq));
+
+        let mut mobility_score = TaperedScore::default();
+        let mut king_attackers = 0;
+        let mut king_attacks = TaperedScore::default();
+        for index in [knight, bishop, rook, queen].iter().copied() {
+            let kind = index % 6;
+            for sq in pieces[index] {
+                let attacks = match kind {
+                    WHITE_KNIGHT => tables.knight_attacks[sq],
+                    WHITE_BISHOP => get_bishop_attack(tables, sq, occupancy),
+                    WHITE_ROOK => get_rook_attack(tables, sq, occupancy),
+                    _ => get_queen_attack(tables, sq, occupancy),
+                }
+                .get();
+                let mobility = (attacks & safe).count_ones() as i32;
+                mobility_score += MOBILITY[kind] * (mobility - MOBILITY_AVERAGE[kind]);
+
+                let attacked = (attacks & zone).count_ones() as i32;
+                if attacked > 0 {
+                    king_attackers += 1;
+                    king_attacks += KING_ZONE_ATTACK[kind] * attacked;
+                }
+            }
+        }
+        let scale = KING_ATTACKERS_SCALE[king_attackers.min(KING_ATTACKERS_SCALE.len() - 1)];
+        let king_attack_score = s(king_attacks.mg * scale / 100, king_attacks.eg * scale / 100);
+
+        let mut piece_score = TaperedScore::default();
+        if pieces[bishop].popcount() >= 2 {
+            piece_score += BISHOP_PAIR;
+        }
+
+        for sq in pieces[knight] {
+            let defended = tables.pawn_attacks[enemy as usize][sq].get() & own_pawns != 0;
+            if (3..=5).contains(&relative_rank(sq, color))
+                && defended
+                && pawn_attack_span(sq, color) & enemy_pawns == 0
+            {
+                piece_score += KNIGHT_OUTPOST;
+            }
+        }
+
+        let enemy_back_rank = match enemy {
+            White => RANK_1,
+            Black => RANK_8,
+        };
+        for sq in pieces[rook] {
+            let file = FILE_MASKS[sq % 8];
+            if file & own_pawns == 0 {
+                piece_score += if file & enemy_pawns == 0 {
+                    ROOK_OPEN_FILE
+                } else {
+                    ROOK_SEMI_OPEN_FILE
+                };
+            }
+            // only worth it if there are pawns to take or the king is cut off
+            if relative_rank(sq, color) == 6
+                && (RANK_MASKS[sq / 8] & enemy_pawns != 0
+                    || enemy_back_rank & pieces[enemy_king].get() != 0)
+            {
+                piece_score += ROOK_ON_SEVENTH;
+            }
+        }
+
+        if let Some(trace) = trace {
+            trace.add(EvalTerm::Mobility, color, mobility_score);
+            trace.add(EvalTerm::KingAttacks, color, king_attack_score);
+            trace.add(EvalTerm::Pieces, color, piece_score);
+        }
+        mobility_score + king_attack_score + piece_score
+    }
+}
@@ -37,6 +37,34 @@ pub fn get_bishop_attack(at: &AttackTables, sq: usize, occupied_positions: BitBo
     at.bishop_attacks[sq][occupancy as usize]
 }
 
+// squares strictly between a and b if they share a rank, file or diagonal, empty otherwise
+#[inline]
+pub fn get_squares_between(at: &AttackTables, a: usize, b: usize) -> BitBoard {
+    // each square blocks the rays of the other, so only the squares in between are seen by both
+    let (a_bit, b_bit) = (BitBoard::new_single_bit(a), BitBoard::new_single_bit(b));
+    if get_rook_attack(at, a, BitBoard::new(0)).get_bit(b) {
+        get_rook_attack(at, a, b_bit) & get_rook_attack(at, b, a_bit)
+    } else if get_bishop_attack(at, a, BitBoard::new(0)).get_bit(b) {
+        get_bishop_attack(at, a, b_bit) & get_bishop_attack(at, b, a_bit)
+    } else {
+        BitBoard::new(0)
+    }
+}
+
+// whole line going through a and b, including both, if they share a rank, file or diagonal
+#[inline]
+pub fn get_line_through(at: &AttackTables, a: usize, b: usize) -> BitBoard {
+    let empty = BitBoard::new(0);
+    let ends = BitBoard::new_single_bit(a) | BitBoard::new_single_bit(b);
+    if get_rook_attack(at, a, empty).get_bit(b) {
+        get_rook_attack(at, a, empty) & get_rook_attack(at, b, empty) | ends
+    } else if get_bishop_attack(at, a, empty).get_bit(b) {
+        get_bishop_attack(at, a, empty) & get_bishop_attack(at, b, empty) | ends
+    } else {
+        empty
+    }
+}
+
 /////***************************/////
 /////**** ATTACK GENERATORS ****/////
 /////***************************/////
@@ -304,7 +304,18 @@ pub fn get_bb_piece_index(p: Piece) -> usize {
     }
 }
 
-use chessire_utils::color::Color::*;
+/// color of the pieces in the bitboard with the given index
+#[inline]
+pub fn get_bb_piece_color(index: usize) -> Color {
+    if index < BLACK_PAWN {
+        White
+    } else {
+        Black
+    }
+}
+
+use crate::engine::Score;
+use chessire_utils::color::Color::{self, *};
 use chessire_utils::piece::Piece::*;
 use chessire_utils::piece::*;
 
@@ -344,85 +355,15 @@ pub const BLACK_PIECES: [usize; 6] = [
 
 pub const BOTH: usize = 2;
 
-//// EVALUATION CONSTANTS ////
-
-pub const KING_VALUE: f32 = 10000.0;
-pub const QUEEN_VALUE: f32 = 1000.0;
-pub const ROOK_VALUE: f32 = 500.0;
-pub const BISHOP_VALUE: f32 = 315.0;
-pub const KNIGHT_VALUE: f32 = 300.0;
-pub const PAWN_VALUE: f32 = 100.0;
-
-//// POSITIONAL TABLES ////
-
-#[rustfmt::skip]
-pub const WHITE_PAWN_SCORES: [i32; 64] = [
-    0,   0,   0,   0,   0,   0,    0,    0,
-    0,   0,   0,   -10, -10, 0,    0,    0,
-    0,   0,   0,   5,   5,   0,    0,    0,
-    5,   5,   10,  20,  20,  10,   5,    0,
-    10,  10,  20,  20,  20,  20,  10,   10,
-    20,  20,  20,  30,  30,  20,  20,   20,
-    30,  30,  30,  40,  40,  30,  30,   30,
-    100, 100, 100, 100, 100, 100, 100, 100,
-];
-
-#[rustfmt::skip]
-pub const BLACK_PAWN_SCORES: [i32; 64] = [
-    100, 100, 100, 100, 100, 100, 100, 100,
-    30,  30,  30,  40,  40,  30,  30,   30,
-    20,  20,  20,  30,  30,  20,  20,   20,
-    10,  10,  20,  20,  20,  20,  10,   10,
-    5,   5,   10,  20,  20,  10,   5,    0,
-    0,   0,   0,   5,   5,   0,    0,    0,
-    0,   0,   0,   -10, -10, 0,    0,    0,
-    0,   0,   0,   0,   0,   0,    0,    0,
-];
-
-#[rustfmt::skip]
-pub const KNIGHT_SCORES: [i32; 64] = [
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-    -5, 10, 10, 10, 10, 10, 10, -5,
-];
-
-#[rustfmt::skip]
-pub const BISHOP_SCORES: [i32; 64] = [
-    12, 11, 11, 11, 11, 11, 11, 12,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    12, 11, 11, 11, 11, 11, 11, 12,
-];
-
-#[rustfmt::skip]
-pub const ROOK_SCORES: [i32; 64] = [
-    12, 11, 11, 11, 11, 11, 11, 12,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    12, 11, 11, 11, 11, 11, 11, 12,
-];
-
-#[rustfmt::skip]
-pub const KING_SCORES: [i32; 64] = [
-    12, 11, 11, 11, 11, 11, 11, 12,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    11, 10, 10, 10, 10, 10, 10, 11,
-    12, 11, 11, 11, 11, 11, 11, 12,
-];
+//// MATERIAL VALUES ////
+// used by the static exchange evaluation, to order captures and to prune them in the
+// quiescence search. They are deliberately kept apart from the tunable values of the
+// evaluation: the ordering and the pruning margins are tuned against these, and don't
+// change behaviour when an EvalFile or a piece value option changes the evaluation
+
+pub const SEE_KING_VALUE: Score = 10000;
+pub const SEE_QUEEN_VALUE: Score = 1000;
+pub const SEE_ROOK_VALUE: Score = 500;
+pub const SEE_BISHOP_VALUE: Score = 315;
+pub const SEE_KNIGHT_VALUE: Score = 300;
+pub const SEE_PAWN_VALUE: Score = 100;
@@ -0,0 +1,78 @@
+use super::evaluation::{s, TaperedScore};
+use super::legality::piece_indices;
+use super::pawns::{ADJACENT_FILES, FILE_MASKS, RANK_MASKS};
+use super::trace::{EvalTerm, EvalTrace};
+use super::{AttackTables, BitBoardEngine};
+use chessire_utils::color::Color::{self, Black, White};
+
+/* King safety, see the chessprogramming wiki for details.
+ * The king is safer behind its own pawns, and in danger on files without them, where the
+ * enemy rooks and queens can reach it. Both only matter while there are pieces left to
+ * attack the king, so they have no endgame weight.
+ * The attacks of the enemy pieces on the king zone are scored with the piece activity.
+ */
+
+/// for each pawn of the king's color on the two ranks in front of it
+const PAWN_SHIELD: TaperedScore = s(12, 0);
+/// for each file without pawns around the king
+const KING_OPEN_FILE: TaperedScore = s(-25, 0);
+/// for each file without pawns of the king's color around the king
+const KING_SEMI_OPEN_FILE: TaperedScore = s(-12, 0);
+
+/// the king square and the squares around it
+#[inline]
+pub fn king_zone(tables: &AttackTables, sq: usize) -> u64 {
+    tables.king_attacks[sq].get() | 1 << sq
+}
+
+impl BitBoardEngine {
+    /// king safety score, from the point of view of white
+    pub fn king_safety(&self) -> TaperedScore {
+        self.king_safety_of(White, None) - self.king_safety_of(Black, None)
+    }
+
+    /// king safety score of the king of color, recording each term in trace if given
+    pub fn king_safety_of(&self, color: Color, trace: Option<&mut EvalTrace>) -> TaperedScore {
+        let (pawn, _, _, _, _, king) = piece_indices(color);
+        let (enemy_pawn, _, _, _, _, _) = piece_indices(color.opponent());
+        let pieces = &self.state.current_position;
+        let own_pawns = pieces[pawn].get();
+        let enemy_pawns = pieces[enemy_pawn].get();
+        let sq = match pieces[king].get_lsb() {
+            Some(sq) => sq,
+            None => return TaperedScore::default(),
+        };
+        let (file, rank) = (sq % 8, sq / 8);
+
+        let mut shield = 0;
+        for distance in 1..=2 {
+            let shield_rank = match color {
+                White => rank + distance,
+                Black => rank.wrapping_sub(distance),
+            };
+            if shield_rank < 8 {
+                shield |= RANK_MASKS[shield_rank];
+            }
+        }
+        shield &= FILE_MASKS[file] | ADJACENT_FILES[file];
+        let shield_score = PAWN_SHIELD * (shield & own_pawns).count_ones() as i32;
+
+        let mut file_score = TaperedScore::default();
+        let around = file.saturating_sub(1)..=(file + 1).min(7);
+        for file in FILE_MASKS[around].iter() {
+            if file & own_pawns == 0 {
+                file_score += if file & enemy_pawns == 0 {
+                    KING_OPEN_FILE
+                } else {
+                    KING_SEMI_OPEN_FILE
+                };
+            }
+        }
+
+        if let Some(trace) = trace {
+            trace.add(EvalTerm::PawnShield, color, shield_score);
+            trace.add(EvalTerm::KingFiles, color, file_score);
+        }
+        shield_score + file_score
+    }
+}
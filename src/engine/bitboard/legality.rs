@@ -0,0 +1,149 @@
+use super::attacks::*;
+use super::constants::*;
+use super::{BitBoard, BitBoardEngine};
+use chessire_utils::color::Color::{self, Black, White};
+
+/* Information needed to generate legal moves without trying them on the board.
+ * It is computed once per position, when the position is set or a move is made:
+ *  - squares attacked by each side, with the sliders seeing through the enemy king so
+ *    the king can't step back along the ray of a checking piece
+ *  - pieces pinned to their own king, they can only move along the pin
+ *  - pieces giving check to the side to move, they must be captured or blocked
+ */
+
+/// bitboard indices of the pieces of col, as (pawn, knight, bishop, rook, queen, king)
+#[inline]
+pub fn piece_indices(col: Color) -> (usize, usize, usize, usize, usize, usize) {
+    match col {
+        White => (
+            WHITE_PAWN,
+            WHITE_KNIGHT,
+            WHITE_BISHOP,
+            WHITE_ROOK,
+            WHITE_QUEEN,
+            WHITE_KING,
+        ),
+        Black => (
+            BLACK_PAWN,
+            BLACK_KNIGHT,
+            BLACK_BISHOP,
+            BLACK_ROOK,
+            BLACK_QUEEN,
+            BLACK_KING,
+        ),
+    }
+}
+
+impl BitBoardEngine {
+    /// fills the position flags, pin maps and attacked squares of the current position
+    pub fn update_legality_info(&mut self) {
+        for col in [White, Black] {
+            self.state.squares_attacked[col as usize] = self.get_attacked_squares(col);
+            self.state.pin_maps[col as usize] = self.get_pinned_pieces(col);
+        }
+
+        let side = self.state.side_to_move;
+        let checkers = self.get_checkers(side);
+        self.state.flags.checkers = checkers;
+        self.state.flags.in_check = checkers.get() != 0;
+        self.state.flags.in_double_check = checkers.popcount() > 1;
+        self.state.flags.pins_exist = self.state.pin_maps[side as usize].get() != 0;
+    }
+
+    /// squares attacked by the pieces of col, as if the enemy king wasn't on the board
+    pub fn get_attacked_squares(&self, col: Color) -> BitBoard {
+        let (pawn, knight, bishop, rook, queen, king) = piece_indices(col);
+        let (.., enemy_king) = piece_indices(col.opponent());
+        let pieces = &self.state.current_position;
+        let tables = &self.attack_tables;
+        let occupancy =
+            BitBoard::new(self.state.occupancies[BOTH].get() & !pieces[enemy_king].get());
+
+        let mut attacks = 0;
+        for sq in pieces[pawn] {
+            attacks |= tables.pawn_attacks[col as usize][sq].get();
+        }
+        for sq in pieces[knight] {
+            attacks |= tables.knight_attacks[sq].get();
+        }
+        for sq in pieces[bishop] | pieces[queen] {
+            attacks |= get_bishop_attack(tables, sq, occupancy).get();
+        }
+        for sq in pieces[rook] | pieces[queen] {
+            attacks |= get_rook_attack(tables, sq, occupancy).get();
+        }
+        for sq in pieces[king] {
+            attacks |= tables.king_attacks[sq].get();
+        }
+        BitBoard::new(attacks)
+    }
+
+    /// pieces of col that can't leave the line between their king and an enemy slider
+    pub fn get_pinned_pieces(&self, col: Color) -> BitBoard {
+        let (.., king) = piece_indices(col);
+        let (_, _, bishop, rook, queen, _) = piece_indices(col.opponent());
+        let pieces = &self.state.current_position;
+        let tables = &self.attack_tables;
+
+        let king_sq = match pieces[king].get_lsb() {
+            Some(sq) => sq,
+            None => return BitBoard::new(0),
+        };
+
+        // enemy sliders that would attack the king if our pieces weren't there
+        let enemies = self.state.occupancies[col.opponent()];
+        let snipers = (get_rook_attack(tables, king_sq, enemies) & (pieces[rook] | pieces[queen]))
+            | (get_bishop_attack(tables, king_sq, enemies) & (pieces[bishop] | pieces[queen]));
+
+        let mut pinned = BitBoard::new(0);
+        for sniper in snipers {
+            let blockers =
+                get_squares_between(tables, king_sq, sniper) & self.state.occupancies[BOTH];
+            // only our own pieces can be in between, the ray would stop at an enemy one
+            if blockers.popcount() == 1 {
+                pinned = pinned | blockers;
+            }
+        }
+        pinned
+    }
+
+    /// enemy pieces attacking the king of col
+    pub fn get_checkers(&self, col: Color) -> BitBoard {
+        let (.., king) = piece_indices(col);
+        let (pawn, knight, bishop, rook, queen, _) = piece_indices(col.opponent());
+        let pieces = &self.state.current_position;
+        let tables = &self.attack_tables;
+        let occupancy = self.state.occupancies[BOTH];
+
+        let king_sq = match pieces[king].get_lsb() {
+            Some(sq) => sq,
+            None => return BitBoard::new(0),
+        };
+        (tables.pawn_attacks[col as usize][king_sq] & pieces[pawn])
+            | (tables.knight_attacks[king_sq] & pieces[knight])
+            | (get_bishop_attack(tables, king_sq, occupancy) & (pieces[bishop] | pieces[queen]))
+            | (get_rook_attack(tables, king_sq, occupancy) & (pieces[rook] | pieces[queen]))
+    }
+
+    /// squares a piece of the side to move can go to without leaving its king in check:
+    /// the checking piece and the squares blocking its ray, or anywhere when not in check
+    pub fn get_evasion_mask(&self) -> BitBoard {
+        let flags = &self.state.flags;
+        if !flags.in_check {
+            return BitBoard::new(u64::MAX);
+        }
+        if flags.in_double_check {
+            return BitBoard::new(0);
+        }
+        let (.., king) = piece_indices(self.state.side_to_move);
+        match (
+            self.state.current_position[king].get_lsb(),
+            flags.checkers.get_lsb(),
+        ) {
+            (Some(king_sq), Some(checker)) => {
+                flags.checkers | get_squares_between(&self.attack_tables, king_sq, checker)
+            }
+            _ => BitBoard::new(0),
+        }
+    }
+}
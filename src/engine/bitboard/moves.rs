@@ -8,9 +8,33 @@ use chessire_utils::moves::*;
 use super::BitBoardEngine;
 use super::Color::*;
 use super::Piece::*;
+use crate::engine::movelist::MoveList;
+use crate::engine::ChessEngine;
 
-pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
-    let mut moves = vec![];
+// Every generator only emits moves to squares set in mask, the legal generator uses it to keep
+// pinned pieces on their pin, answer checks, and keep the king away from attacked squares.
+// It's also used to generate captures or quiet moves only.
+
+/// Kind of moves to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenType {
+    All,
+    /// captures, en passant and promotions
+    Captures,
+    /// everything else, castling included
+    Quiets,
+}
+
+pub fn get_pawn_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    gen: GenType,
+    moves: &mut MoveList,
+) {
+    let quiets = gen != GenType::Captures;
+    let captures = gen != GenType::Quiets;
 
     let is_start = is_start_rank(source_square, color);
     let is_promotion = is_promotion_rank(source_square, color);
@@ -21,10 +45,13 @@ pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
     //*** QUIET MOVES ***//
 
     // double push first
-    if is_start {
+    if is_start && quiets {
         let double_push_target = get_double_push_target(source_square, color);
         // if both squares are empty
-        if is_empty(bb, single_push_target) && is_empty(bb, double_push_target) {
+        if is_empty(bb, single_push_target)
+            && is_empty(bb, double_push_target)
+            && mask.get_bit(double_push_target)
+        {
             moves.push(Move::new_pawn_double_push(
                 color,
                 Coord::from_tile(source_square),
@@ -34,7 +61,11 @@ pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
     let source_square = Coord::from_tile(source_square);
     // promotions
     // If we are on the last rank and the next square is empty
-    if is_promotion && is_empty(bb, single_push_target) {
+    if captures
+        && is_promotion
+        && is_empty(bb, single_push_target)
+        && mask.get_bit(single_push_target)
+    {
         moves.push(Move::new_promotion(color, source_square, Queen(color)));
         moves.push(Move::new_promotion(color, source_square, Rook(color)));
         moves.push(Move::new_promotion(color, source_square, Bishop(color)));
@@ -43,10 +74,17 @@ pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
 
     // single push
     // if the next square is empty, the promotion rank should be ignored to avoid dupes
-    if !is_promotion && is_empty(bb, single_push_target) {
+    if quiets
+        && !is_promotion
+        && is_empty(bb, single_push_target)
+        && mask.get_bit(single_push_target)
+    {
         moves.push(Move::new_pawn_push(color, source_square));
     }
     //*** CAPTURES ***//
+    if !captures {
+        return;
+    }
     if let Some(enpassant_target) = bb.state.enpassant {
         // and with the attack tables of the current pawn
         if (BitBoard::new_single_bit(enpassant_target)
@@ -66,13 +104,18 @@ pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
             .castling(false)
             .double_push(false);
 
-            moves.push(m);
+            // the captured pawn isn't on the target square, and both pawns leave the same rank,
+            // so it's simpler to check this one on the board
+            if bb.test_move_legality(m).is_ok() {
+                moves.push(m);
+            }
         }
     }
     // time to consider attacks
     // get valid attacks where there's an enemy piece
     let mut attacks = bb.attack_tables.pawn_attacks[side][source_square.to_usize()]
-        & bb.state.occupancies[color.opponent() as usize];
+        & bb.state.occupancies[color.opponent() as usize]
+        & mask;
 
     // loop over all set bits
     while let Some(target_square) = attacks.get_lsb() {
@@ -99,41 +142,49 @@ pub fn get_pawn_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
         }
         attacks.reset_bit(target_square.to_usize());
     }
-    moves
 }
 
-pub fn get_knight_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
-    let free_squares = bb.attack_tables.knight_attacks[source_square] & !bb.state.occupancies[BOTH];
-    let enemy_squares = bb.attack_tables.knight_attacks[source_square]
-        & bb.state.occupancies[color.opponent() as usize];
+pub fn get_knight_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    moves: &mut MoveList,
+) {
+    let attacks = bb.attack_tables.knight_attacks[source_square] & mask;
+    let free_squares = attacks & !bb.state.occupancies[BOTH];
+    let enemy_squares = attacks & bb.state.occupancies[color.opponent() as usize];
 
-    enemy_squares
-        .into_iter()
-        .map(|target| {
-            Move::new_knight_move(
-                Coord::from_tile(source_square),
-                Coord::from_tile(target),
-                color,
-                true,
-            )
-        })
-        .chain(free_squares.into_iter().map(|target| {
-            Move::new_knight_move(
-                Coord::from_tile(source_square),
-                Coord::from_tile(target),
-                color,
-                false,
-            )
-        }))
-        .collect()
+    for target in enemy_squares {
+        moves.push(Move::new_knight_move(
+            Coord::from_tile(source_square),
+            Coord::from_tile(target),
+            color,
+            true,
+        ));
+    }
+    for target in free_squares {
+        moves.push(Move::new_knight_move(
+            Coord::from_tile(source_square),
+            Coord::from_tile(target),
+            color,
+            false,
+        ));
+    }
 }
 
 use super::attacks::*;
 
 //TODO: this can probably be refactor into something better
-pub fn get_bishop_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
+pub fn get_bishop_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    moves: &mut MoveList,
+) {
     let bishop_attacks =
-        get_bishop_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]);
+        get_bishop_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]) & mask;
 
     let free_squares = bishop_attacks & !bb.state.occupancies[BOTH];
     let enemy_squares = bishop_attacks & bb.state.occupancies[color.opponent() as usize];
@@ -144,10 +195,18 @@ pub fn get_bishop_moves(bb: &BitBoardEngine, source_square: usize, color: Color)
         enemy_squares,
         source_square,
         color,
-    )
+        moves,
+    );
 }
-pub fn get_rook_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
-    let attacks = get_rook_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]);
+pub fn get_rook_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    moves: &mut MoveList,
+) {
+    let attacks =
+        get_rook_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]) & mask;
 
     let free_squares = attacks & !bb.state.occupancies[BOTH];
     let enemy_squares = attacks & bb.state.occupancies[color.opponent() as usize];
@@ -158,10 +217,18 @@ pub fn get_rook_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
         enemy_squares,
         source_square,
         color,
-    )
+        moves,
+    );
 }
-pub fn get_queen_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
-    let attacks = get_queen_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]);
+pub fn get_queen_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    moves: &mut MoveList,
+) {
+    let attacks =
+        get_queen_attack(&bb.attack_tables, source_square, bb.state.occupancies[BOTH]) & mask;
 
     let free_squares = attacks & !bb.state.occupancies[BOTH];
     let enemy_squares = attacks & bb.state.occupancies[color.opponent() as usize];
@@ -172,34 +239,44 @@ pub fn get_queen_moves(bb: &BitBoardEngine, source_square: usize, color: Color)
         enemy_squares,
         source_square,
         color,
-    )
+        moves,
+    );
 }
-pub fn get_king_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -> Vec<Move> {
+pub fn get_king_moves(
+    bb: &BitBoardEngine,
+    source_square: usize,
+    color: Color,
+    mask: BitBoard,
+    moves: &mut MoveList,
+) {
     use super::*;
 
     // First regular moves
-    let attacks = bb.attack_tables.king_attacks[source_square];
+    let attacks = bb.attack_tables.king_attacks[source_square] & mask;
 
     let free_squares = attacks & !bb.state.occupancies[BOTH];
     let enemy_squares = attacks & bb.state.occupancies[color.opponent() as usize];
 
-    let mut moves = fill_movelist(
+    fill_movelist(
         King(color),
         free_squares,
         enemy_squares,
         source_square,
         color,
+        moves,
     );
 
+    // castling is only generated if the king target square is in the mask
     if color == White {
         // White castling moves
         if bb.state.castling_rights.white_king_side
+            && mask.get_bit(index_from_bitmask(G1))
             && is_empty(bb, index_from_bitmask(F1))
             && is_empty(bb, index_from_bitmask(G1))
-            && !bb.is_square_attacked_by(index_from_bitmask(F1), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(G1), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(F1), color)
+            && !is_attacked(bb, index_from_bitmask(G1), color)
             // if the king is in check, no castling!
-            && !bb.is_square_attacked_by(index_from_bitmask(E1), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(E1), color)
         {
             let mov = Move::new_castling(
                 Coord::from_tile(index_from_bitmask(E1)),
@@ -209,14 +286,15 @@ pub fn get_king_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
             moves.push(mov);
         }
         if bb.state.castling_rights.white_queen_side
+            && mask.get_bit(index_from_bitmask(C1))
             && is_empty(bb, index_from_bitmask(B1))
             && is_empty(bb, index_from_bitmask(C1))
             && is_empty(bb, index_from_bitmask(D1))
-//            && !bb.is_square_attacked_by(index_from_bitmask(B1), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(C1), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(D1), color.opponent()) 
+//            && !is_attacked(bb, index_from_bitmask(B1), color)
+            && !is_attacked(bb, index_from_bitmask(C1), color)
+            && !is_attacked(bb, index_from_bitmask(D1), color) 
             // if the king is in check, no castling!
-            && !bb.is_square_attacked_by(index_from_bitmask(E1), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(E1), color)
         {
             let mov = Move::new_castling(
                 Coord::from_tile(index_from_bitmask(E1)),
@@ -227,12 +305,13 @@ pub fn get_king_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
         }
     } else {
         if bb.state.castling_rights.black_king_side
+            && mask.get_bit(index_from_bitmask(G8))
             && is_empty(bb, index_from_bitmask(F8))
             && is_empty(bb, index_from_bitmask(G8))
-            && !bb.is_square_attacked_by(index_from_bitmask(F8), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(G8), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(F8), color)
+            && !is_attacked(bb, index_from_bitmask(G8), color)
             // if the king is in check, no castling!
-            && !bb.is_square_attacked_by(index_from_bitmask(E8), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(E8), color)
  
         {
             //CHECK: manual move from e8 to g8
@@ -244,14 +323,15 @@ pub fn get_king_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
             moves.push(mov);
         }
         if bb.state.castling_rights.black_queen_side
+            && mask.get_bit(index_from_bitmask(C8))
             && is_empty(bb, index_from_bitmask(B8))
             && is_empty(bb, index_from_bitmask(C8))
             && is_empty(bb, index_from_bitmask(D8))
-//            && !bb.is_square_attacked_by(index_from_bitmask(B8), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(C8), color.opponent())
-            && !bb.is_square_attacked_by(index_from_bitmask(D8), color.opponent())
+//            && !is_attacked(bb, index_from_bitmask(B8), color)
+            && !is_attacked(bb, index_from_bitmask(C8), color)
+            && !is_attacked(bb, index_from_bitmask(D8), color)
             // if the king is in check, no castling!
-            && !bb.is_square_attacked_by(index_from_bitmask(E8), color.opponent())
+            && !is_attacked(bb, index_from_bitmask(E8), color)
  
         {
             let mov = Move::new_castling(
@@ -262,8 +342,6 @@ pub fn get_king_moves(bb: &BitBoardEngine, source_square: usize, color: Color) -
             moves.push(mov);
         }
     }
-
-    moves
 }
 
 // helper functions
@@ -302,16 +380,21 @@ fn is_empty(bb: &BitBoardEngine, square: usize) -> bool {
     !bb.state.occupancies[BOTH].get_bit(square)
 }
 
+// true if the enemies of color attack the square
+fn is_attacked(bb: &BitBoardEngine, square: usize, color: Color) -> bool {
+    bb.state.squares_attacked[color.opponent() as usize].get_bit(square)
+}
+
 fn fill_movelist(
     piece: Piece,
     free_squares: BitBoard,
     enemy_squares: BitBoard,
     source_square: usize,
     _color: Color,
-) -> Vec<Move> {
-    enemy_squares
-        .into_iter()
-        .map(|target| {
+    moves: &mut MoveList,
+) {
+    for target in enemy_squares {
+        moves.push(
             Move::new(
                 Coord::from_tile(source_square),
                 Coord::from_tile(target),
@@ -321,9 +404,11 @@ fn fill_movelist(
             .capture(true)
             .enpassant(false)
             .castling(false)
-            .double_push(false)
-        })
-        .chain(free_squares.into_iter().map(|target| {
+            .double_push(false),
+        );
+    }
+    for target in free_squares {
+        moves.push(
             Move::new(
                 Coord::from_tile(source_square),
                 Coord::from_tile(target),
@@ -333,7 +418,7 @@ fn fill_movelist(
             .capture(false)
             .double_push(false)
             .castling(false)
-            .enpassant(false)
-        }))
-        .collect()
+            .enpassant(false),
+        );
+    }
 }
@@ -0,0 +1,289 @@
+use super::constants::*;
+use super::search::MAX_PLY;
+use super::transposition::TtMove;
+use super::BitBoardEngine;
+use crate::engine::movelist::MoveList;
+use crate::engine::{same_move, Score};
+use chessire_utils::color::Color;
+use chessire_utils::moves::Move;
+
+/* Move ordering, see the chessprogramming wiki for details.
+ * Alpha-beta cuts off sooner the earlier the best move is searched, so the moves are
+ * searched in this order:
+ *  - the best move stored in the transposition table
+ *  - captures and promotions that don't lose material, most valuable victim first
+ *  - the two killer moves of the ply, quiet moves that caused a cutoff in a sibling node
+ *  - the countermove, the quiet move that last refuted the move just played
+ *  - the rest of the quiet moves, by how often they caused cutoffs (history heuristic)
+ *  - captures that lose material
+ * Each group is only generated when the previous ones have been searched, most nodes cut
+ * off with one of the first moves and never pay for generating and ordering the rest.
+ */
+
+// history scores stay within +-MAX_HISTORY
+const MAX_HISTORY: i32 = 16_384;
+
+/// fixed material value of the piece on a bitboard, used to order and prune captures
+#[inline]
+pub fn piece_value(index: usize) -> Score {
+    match index % 6 {
+        WHITE_PAWN => SEE_PAWN_VALUE,
+        WHITE_KNIGHT => SEE_KNIGHT_VALUE,
+        WHITE_BISHOP => SEE_BISHOP_VALUE,
+        WHITE_ROOK => SEE_ROOK_VALUE,
+        WHITE_QUEEN => SEE_QUEEN_VALUE,
+        _ => 0,
+    }
+}
+
+/// true for the moves searched by the quiescence search
+#[inline]
+pub fn is_tactical(mov: Move) -> bool {
+    mov.capture || mov.enpassant || mov.promoted_piece.is_some()
+}
+
+/// most valuable victim, least valuable attacker
+pub fn mvv_lva(engine: &BitBoardEngine, mov: Move) -> i32 {
+    let victim = engine.captured_piece(mov).map_or(0, piece_value);
+    let promotion = mov
+        .promoted_piece
+        .map_or(0, |p| piece_value(get_bb_piece_index(p)));
+    10 * (victim + promotion) - piece_value(get_bb_piece_index(mov.piece))
+}
+
+/// Statistics about the quiet moves that caused cutoffs, kept from one search to the
+/// next until a new game starts
+#[derive(Debug, Clone)]
+pub struct OrderingTables {
+    /// two quiet moves that caused a cutoff at each ply
+    pub killers: [[Option<Move>; 2]; MAX_PLY],
+    /// butterfly history, indexed by side, source and target squares
+    pub history: [[[i32; 64]; 64]; 2],
+    /// quiet move that refuted a move, indexed by its bitboard and target square
+    pub countermoves: [[Option<Move>; 64]; 12],
+}
+
+impl Default for OrderingTables {
+    fn default() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+            countermoves: [[None; 64]; 12],
+        }
+    }
+}
+
+impl OrderingTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    #[inline]
+    pub fn history_score(&self, side: Color, mov: Move) -> i32 {
+        self.history[side as usize][mov.source.to_usize()][mov.target.to_usize()]
+    }
+
+    #[inline]
+    pub fn countermove(&self, previous: Move) -> Option<Move> {
+        self.countermoves[get_bb_piece_index(previous.piece)][previous.target.to_usize()]
+    }
+
+    /// the quiet move mov caused a cutoff at ply after the quiets tried before it failed,
+    /// previous is the move that lead to the position
+    pub fn update_quiet_stats(
+        &mut self,
+        side: Color,
+        mov: Move,
+        tried: &MoveList,
+        previous: Option<Move>,
+        ply: usize,
+        depth: usize,
+    ) {
+        let killers = &mut self.killers[ply];
+        if !killers[0].is_some_and(|k| same_move(k, mov)) {
+            killers[1] = killers[0];
+            killers[0] = Some(mov);
+        }
+
+        if let Some(previous) = previous {
+            self.countermoves[get_bb_piece_index(previous.piece)][previous.target.to_usize()] =
+                Some(mov);
+        }
+
+        // deeper cutoffs say more about the move
+        let bonus = (depth * depth).min(MAX_HISTORY as usize) as i32;
+        self.update_history(side, mov, bonus);
+        for quiet in tried.iter().filter(|m| !same_move(*m, mov)) {
+            self.update_history(side, quiet, -bonus);
+        }
+    }
+
+    /// the more extreme the score already is, the less it changes,
+    /// so it stays within +-MAX_HISTORY
+    #[inline]
+    fn update_history(&mut self, side: Color, mov: Move, bonus: i32) {
+        let entry = &mut self.history[side as usize][mov.source.to_usize()][mov.target.to_usize()];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+/// Groups of moves handed out by the move picker, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    TtMove,
+    GenerateCaptures,
+    GoodCaptures,
+    Refutations,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Hands out the legal moves of a position from the most to the least promising,
+/// generating them in stages
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<TtMove>,
+    /// the transposition table move, once found legal
+    tt_legal: Option<Move>,
+    /// the two killers followed by the countermove, the illegal ones are dropped
+    refutations: [Option<Move>; 3],
+    /// moves of the current stage
+    moves: MoveList,
+    index: usize,
+    /// captures that lose material, left for the end
+    bad_captures: MoveList,
+}
+
+impl MovePicker {
+    /// picker for the current position of engine at ply
+    pub fn new(engine: &BitBoardEngine, tt_move: Option<TtMove>, ply: usize) -> Self {
+        let tables = &engine.ordering;
+        let killers = tables.killers[ply.min(MAX_PLY - 1)];
+        let countermove = engine
+            .history
+            .last()
+            .and_then(|undo| undo.mov)
+            .and_then(|previous| tables.countermove(previous));
+        Self {
+            stage: Stage::TtMove,
+            tt_move,
+            tt_legal: None,
+            refutations: [killers[0], killers[1], countermove],
+            moves: MoveList::new(),
+            index: 0,
+            bad_captures: MoveList::new(),
+        }
+    }
+
+    /// next move to search, None once every legal move has been handed out
+    pub fn next(&mut self, engine: &BitBoardEngine) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TtMove => {
+                    self.stage = Stage::GenerateCaptures;
+                    // the table can hold a move of another position with the same bucket
+                    self.tt_legal = self.tt_move.and_then(|tt_move| {
+                        engine
+                            .get_moves_from(tt_move.source())
+                            .iter()
+                            .find(|mov| tt_move.matches(*mov))
+                    });
+                    if self.tt_legal.is_some() {
+                        return self.tt_legal;
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    self.moves = engine.get_captures();
+                    self.moves.score_moves(|mov| mvv_lva(engine, mov));
+                    self.index = 0;
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => match self.moves.pick_best(self.index) {
+                    Some(mov) => {
+                        self.index += 1;
+                        if self.is_tt_move(mov) {
+                            continue;
+                        }
+                        // the exchange is only evaluated once the capture is reached
+                        if !engine.see_ge(mov, 0) {
+                            self.bad_captures.push(mov);
+                            continue;
+                        }
+                        return Some(mov);
+                    }
+                    None => {
+                        self.index = 0;
+                        self.stage = Stage::Refutations;
+                    }
+                },
+                Stage::Refutations => {
+                    if self.index == self.refutations.len() {
+                        self.stage = Stage::GenerateQuiets;
+                        continue;
+                    }
+                    let index = self.index;
+                    self.index += 1;
+                    // they come from sibling or unrelated positions, they may not be legal here
+                    let refutation = self.refutations[index].filter(|mov| {
+                        !self.is_tt_move(*mov) && !contains_move(&self.refutations[..index], *mov)
+                    });
+                    self.refutations[index] = refutation.and_then(|refutation| {
+                        engine
+                            .get_moves_from(refutation.source.to_usize())
+                            .iter()
+                            .find(|mov| same_move(*mov, refutation) && !is_tactical(*mov))
+                    });
+                    if self.refutations[index].is_some() {
+                        return self.refutations[index];
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    let side = engine.state.side_to_move;
+                    self.moves = engine.get_quiets();
+                    self.moves
+                        .score_moves(|mov| engine.ordering.history_score(side, mov));
+                    self.index = 0;
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => match self.moves.pick_best(self.index) {
+                    Some(mov) => {
+                        self.index += 1;
+                        if self.is_tt_move(mov) || contains_move(&self.refutations, mov) {
+                            continue;
+                        }
+                        return Some(mov);
+                    }
+                    None => {
+                        self.index = 0;
+                        self.stage = Stage::BadCaptures;
+                    }
+                },
+                Stage::BadCaptures => {
+                    if self.index < self.bad_captures.len() {
+                        self.index += 1;
+                        return Some(self.bad_captures.get(self.index - 1));
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    #[inline]
+    fn is_tt_move(&self, mov: Move) -> bool {
+        self.tt_legal.is_some_and(|m| same_move(m, mov))
+    }
+}
+
+/// true if mov is one of moves
+#[inline]
+fn contains_move(moves: &[Option<Move>], mov: Move) -> bool {
+    moves.iter().flatten().any(|m| same_move(*m, mov))
+}
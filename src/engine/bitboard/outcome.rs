@@ -0,0 +1,116 @@
+use super::constants::*;
+use super::BitBoardEngine;
+use chessire_utils::color::Color;
+
+/* Game endings.
+ * Besides checkmate and stalemate the game is drawn when:
+ *  - the same position is reached for the third time, positions are compared by their
+ *    zobrist key, which the undo records of the moves played keep for every earlier position
+ *  - fifty moves by each side are played without a capture or a pawn move
+ *  - neither side has enough material to mate: KvK, KBvK, KNvK or KBvKB with both
+ *    bishops on squares of the same colour
+ */
+
+// the fifty move rule counts plies
+const FIFTY_MOVES_PLIES: u32 = 100;
+
+/// How a game has ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOutcome {
+    /// the winner
+    Checkmate(Color),
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    InsufficientMaterial,
+}
+
+impl BitBoardEngine {
+    /// the outcome of the game if it's over in the current position
+    pub fn game_outcome(&self) -> Option<GameOutcome> {
+        let side = self.state.side_to_move;
+        // a mate on the last move before the fifty move rule is still a mate
+        if self.get_legal_moves().is_empty() {
+            return Some(if self.state.flags.in_check {
+                GameOutcome::Checkmate(side.opponent())
+            } else {
+                GameOutcome::Stalemate
+            });
+        }
+        if self.is_repetition(0) {
+            Some(GameOutcome::Repetition)
+        } else if self.is_fifty_moves_draw() {
+            Some(GameOutcome::FiftyMoves)
+        } else if self.is_insufficient_material() {
+            Some(GameOutcome::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+
+    /// true if the position was already reached after the root of a search at ply, or
+    /// twice before it. Positions inside the search only have to repeat once to be scored as
+    /// draws, since the side that repeats them could do it again
+    pub fn is_repetition(&self, ply: usize) -> bool {
+        let reversible = (self.state.halfmove_clock as usize).min(self.history.len());
+        let mut repetitions = 0;
+        for (plies_ago, undo) in self.history.iter().rev().take(reversible).enumerate() {
+            let plies_ago = plies_ago + 1;
+            // the position before a null move wasn't really reached
+            if undo.mov.is_none() {
+                break;
+            }
+            // only positions with the same side to move can be the same
+            if plies_ago % 2 == 0 && undo.hash == self.state.hash {
+                if plies_ago <= ply {
+                    return true;
+                }
+                repetitions += 1;
+                if repetitions >= 2 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// true once fifty moves have been played without captures or pawn moves
+    #[inline]
+    pub fn is_fifty_moves_draw(&self) -> bool {
+        self.state.halfmove_clock >= FIFTY_MOVES_PLIES
+    }
+
+    /// true if neither side can ever mate, whatever the moves played
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = &self.state.current_position;
+        let heavy = pieces[WHITE_PAWN].get()
+            | pieces[BLACK_PAWN].get()
+            | pieces[WHITE_ROOK].get()
+            | pieces[BLACK_ROOK].get()
+            | pieces[WHITE_QUEEN].get()
+            | pieces[BLACK_QUEEN].get();
+        if heavy != 0 {
+            return false;
+        }
+
+        let knights = pieces[WHITE_KNIGHT].popcount() + pieces[BLACK_KNIGHT].popcount();
+        let white_bishops = pieces[WHITE_BISHOP];
+        let black_bishops = pieces[BLACK_BISHOP];
+        match (knights, white_bishops.popcount(), black_bishops.popcount()) {
+            // a lone minor piece
+            (0, 0, 0) | (1, 0, 0) | (0, 1, 0) | (0, 0, 1) => true,
+            // bishops that can never attack each other's squares
+            (0, 1, 1) => match (white_bishops.get_lsb(), black_bishops.get_lsb()) {
+                (Some(a), Some(b)) => square_colour(a) == square_colour(b),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// 0 for dark squares, 1 for light squares
+#[inline]
+fn square_colour(sq: usize) -> usize {
+    (sq / 8 + sq % 8) % 2
+}
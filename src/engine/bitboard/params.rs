@@ -0,0 +1,229 @@
+use super::constants::*;
+use super::evaluation::*;
+use crate::engine::Score;
+use chessire_utils::color::Color::{Black, White};
+use std::fmt;
+
+/* Evaluation parameters.
+ * The material values and the piece square tables of the evaluation can be changed at
+ * runtime, to try changes to the evaluation without recompiling. The values built into the
+ * engine are the defaults.
+ * Parameters are written as text, a name followed by its values:
+ *     KnightValue 337 281     middlegame and endgame material value
+ *     KnightMg 0 -89 ...      the 64 squares of a piece square table, from a8 to h1 as
+ *                             seen by white, the material value isn't included
+ *     KnightEg.e4 22          a single square of a table
+ * Anything after a # is a comment, and parameters that aren't given keep their value.
+ */
+
+const PIECE_NAMES: [&str; 6] = ["Pawn", "Knight", "Bishop", "Rook", "Queen", "King"];
+
+/// A parameter, as named in the text format
+enum Param {
+    /// material value of a piece
+    Value(usize),
+    /// piece square table of a piece, middlegame or endgame
+    Table(usize, bool),
+    /// square of the piece square table of a piece, middlegame or endgame
+    Square(usize, bool, usize),
+}
+
+impl Param {
+    fn parse(name: &str) -> Option<Self> {
+        let (name, square) = match name.split_once('.') {
+            Some((name, square)) => (name, Some(square)),
+            None => (name, None),
+        };
+        let piece = PIECE_NAMES.iter().position(|p| name.starts_with(p))?;
+        let endgame = match &name[PIECE_NAMES[piece].len()..] {
+            "Value" if square.is_none() => return Some(Param::Value(piece)),
+            "Mg" => false,
+            "Eg" => true,
+            _ => return None,
+        };
+        match square {
+            Some(square) => {
+                let sq = SQUARE_NAMES.iter().position(|s| *s == square)?;
+                // the tables start at the eighth rank
+                Some(Param::Square(piece, endgame, sq ^ 56))
+            }
+            None => Some(Param::Table(piece, endgame)),
+        }
+    }
+
+    /// how many values follow the name
+    fn len(&self) -> usize {
+        match self {
+            Param::Value(_) => 2,
+            Param::Table(..) => 64,
+            Param::Square(..) => 1,
+        }
+    }
+}
+
+/// Material values and piece square tables used by the evaluation
+#[derive(Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    /// material values, indexed by piece
+    values: [TaperedScore; 6],
+    /// piece square tables, indexed by piece, written from white's side of the board
+    tables: [[TaperedScore; 64]; 6],
+    /// material and piece square table score from the point of view of white,
+    /// indexed by bitboard and square, built from the values and the tables
+    psqt: [[TaperedScore; 64]; 12],
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        let mut params = Self {
+            values: [TaperedScore::default(); 6],
+            tables: [[TaperedScore::default(); 64]; 6],
+            psqt: [[TaperedScore::default(); 64]; 12],
+        };
+        for piece in 0..6 {
+            params.values[piece] = s(MG_PIECE_VALUES[piece], EG_PIECE_VALUES[piece]);
+            for sq in 0..64 {
+                params.tables[piece][sq] = s(MG_TABLES[piece][sq], EG_TABLES[piece][sq]);
+            }
+        }
+        params.update();
+        params
+    }
+}
+
+// printing every table is never useful
+impl fmt::Debug for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvalParams")
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl EvalParams {
+    /// material and piece square table score of the piece of a bitboard on sq,
+    /// from the point of view of white
+    #[inline]
+    pub fn psqt(&self, piece: usize, sq: usize) -> TaperedScore {
+        self.psqt[piece][sq]
+    }
+
+    /// material value of a piece
+    pub fn piece_value(&self, piece: usize) -> TaperedScore {
+        self.values[piece % 6]
+    }
+
+    /// piece square table score of the piece of a bitboard on sq, for its color
+    pub fn piece_square(&self, piece: usize, sq: usize) -> TaperedScore {
+        match get_bb_piece_color(piece) {
+            // the tables start at the eighth rank, while square 0 is a1
+            White => self.tables[piece % 6][sq ^ 56],
+            // black sees the board from the other side
+            Black => self.tables[piece % 6][sq],
+        }
+    }
+
+    pub fn set_piece_value(&mut self, piece: usize, value: TaperedScore) {
+        self.values[piece % 6] = value;
+        self.update();
+    }
+
+    /// rebuilds the score of every piece on every square
+    fn update(&mut self) {
+        for piece in 0..12 {
+            for sq in 0..64 {
+                let score = self.piece_value(piece) + self.piece_square(piece, sq);
+                self.psqt[piece][sq] = match get_bb_piece_color(piece) {
+                    White => score,
+                    Black => s(-score.mg, -score.eg),
+                };
+            }
+        }
+    }
+
+    /// changes the parameters given in text, if any of them is wrong nothing changes
+    pub fn apply(&mut self, text: &str) -> Result<(), String> {
+        let mut params = self.clone();
+        let mut tokens = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_ascii_whitespace());
+
+        while let Some(name) = tokens.next() {
+            let param = Param::parse(name).ok_or_else(|| format!("unknown parameter {}", name))?;
+            let mut values = Vec::with_capacity(param.len());
+            for _ in 0..param.len() {
+                let value = tokens
+                    .next()
+                    .ok_or_else(|| format!("missing values for parameter {}", name))?;
+                let value = value
+                    .parse::<Score>()
+                    .map_err(|_| format!("invalid value {} for parameter {}", value, name))?;
+                values.push(value);
+            }
+
+            match param {
+                Param::Value(piece) => params.values[piece] = s(values[0], values[1]),
+                Param::Table(piece, endgame) => {
+                    for (entry, value) in params.tables[piece].iter_mut().zip(values) {
+                        set_phase(entry, endgame, value);
+                    }
+                }
+                Param::Square(piece, endgame, sq) => {
+                    set_phase(&mut params.tables[piece][sq], endgame, values[0])
+                }
+            }
+        }
+        params.update();
+        *self = params;
+        Ok(())
+    }
+
+    /// the default parameters, with the changes of the file at path
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+        let mut params = Self::default();
+        params.apply(&text)?;
+        Ok(params)
+    }
+
+    /// writes every parameter to the file at path
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_string())
+            .map_err(|e| format!("unable to write {}: {}", path, e))
+    }
+}
+
+#[inline]
+fn set_phase(entry: &mut TaperedScore, endgame: bool, value: Score) {
+    if endgame {
+        entry.eg = value;
+    } else {
+        entry.mg = value;
+    }
+}
+
+/// writes the parameters in the text format read by apply
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# material values, middlegame and endgame")?;
+        for (name, value) in PIECE_NAMES.iter().zip(self.values.iter()) {
+            writeln!(f, "{}Value {} {}", name, value.mg, value.eg)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "# piece square tables, from a8 to h1 as seen by white")?;
+        for (name, table) in PIECE_NAMES.iter().zip(self.tables.iter()) {
+            for endgame in [false, true].iter().copied() {
+                writeln!(f, "{}{}", name, if endgame { "Eg" } else { "Mg" })?;
+                for rank in table.chunks(8) {
+                    for entry in rank {
+                        write!(f, " {:>4}", if endgame { entry.eg } else { entry.mg })?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
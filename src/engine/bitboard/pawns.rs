@@ -0,0 +1,261 @@
+use super::constants::*;
+use super::evaluation::{s, TaperedScore};
+use super::trace::{EvalTerm, EvalTrace};
+use super::BitBoardEngine;
+use crate::engine::Score;
+use chessire_utils::color::Color::{self, Black, White};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/* Pawn structure, see the chessprogramming wiki for details.
+ * Pawns are scored by the shape of the pawn chains:
+ *  - doubled pawns, with another pawn of the same color in front of them on their file
+ *  - isolated pawns, without pawns of the same color on the adjacent files
+ *  - backward pawns, behind the pawns on the adjacent files and unable to advance safely
+ *  - connected pawns, defended by a pawn or next to one on the same rank
+ *  - passed pawns, with no enemy pawns in front of them on their file or the adjacent ones
+ * Pawns move seldom, so the score only depends on the pawns and is kept in a hash table
+ * indexed by the zobrist key of the pawns, which hits for most positions of a search.
+ */
+
+const DOUBLED: TaperedScore = s(-10, -25);
+const ISOLATED: TaperedScore = s(-8, -15);
+const BACKWARD: TaperedScore = s(-8, -12);
+
+// indexed by rank, as seen from the side of the pawn
+#[rustfmt::skip]
+const CONNECTED: [TaperedScore; 8] = [
+    s(0, 0), s(3, 2), s(5, 4), s(8, 6), s(15, 10), s(25, 15), s(40, 25), s(0, 0),
+];
+// passed pawns are stronger the closer they are to promoting, and the fewer pieces are
+// left to stop them
+#[rustfmt::skip]
+const PASSED: [TaperedScore; 8] = [
+    s(0, 0), s(5, 10), s(10, 20), s(15, 30), s(25, 50), s(45, 80), s(70, 125), s(0, 0),
+];
+
+pub const FILE_MASKS: [u64; 8] = [
+    FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
+];
+pub const RANK_MASKS: [u64; 8] = [
+    RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8,
+];
+
+/// files next to each file
+pub const ADJACENT_FILES: [u64; 8] = adjacent_files();
+
+/// ranks in front of each rank, as seen by each color
+pub const FORWARD_RANKS: [[u64; 8]; 2] = forward_ranks();
+
+const fn adjacent_files() -> [u64; 8] {
+    let mut masks = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        if file > 0 {
+            masks[file] |= FILE_MASKS[file - 1];
+        }
+        if file < 7 {
+            masks[file] |= FILE_MASKS[file + 1];
+        }
+        file += 1;
+    }
+    masks
+}
+
+const fn forward_ranks() -> [[u64; 8]; 2] {
+    let mut masks = [[0; 8]; 2];
+    let mut rank = 0;
+    while rank < 8 {
+        let mut other = 0;
+        while other < 8 {
+            if other > rank {
+                masks[White as usize][rank] |= RANK_MASKS[other];
+            } else if other < rank {
+                masks[Black as usize][rank] |= RANK_MASKS[other];
+            }
+            other += 1;
+        }
+        rank += 1;
+    }
+    masks
+}
+
+/// rank of sq as seen from the side of color, 0 for its back rank
+#[inline]
+pub fn relative_rank(sq: usize, color: Color) -> usize {
+    match color {
+        White => sq / 8,
+        Black => 7 - sq / 8,
+    }
+}
+
+/// squares in front of sq on its file and the adjacent ones, as seen by color
+#[inline]
+pub fn passed_pawn_mask(sq: usize, color: Color) -> u64 {
+    let file = sq % 8;
+    FORWARD_RANKS[color as usize][sq / 8] & (FILE_MASKS[file] | ADJACENT_FILES[file])
+}
+
+/// squares in front of sq on the adjacent files, as seen by color. Enemy pawns on them
+/// can attack sq once they advance
+#[inline]
+pub fn pawn_attack_span(sq: usize, color: Color) -> u64 {
+    FORWARD_RANKS[color as usize][sq / 8] & ADJACENT_FILES[sq % 8]
+}
+
+impl BitBoardEngine {
+    /// pawn structure score, from the point of view of white
+    pub fn pawn_structure(&self) -> TaperedScore {
+        let key = self.state.pawn_hash;
+        if let Some(score) = self.pawn_table.probe(key) {
+            return score;
+        }
+        let score = self.pawn_structure_of(White, None) - self.pawn_structure_of(Black, None);
+        self.pawn_table.store(key, score);
+        score
+    }
+
+    /// pawn structure score of the pawns of color, recording each term in trace if given
+    pub fn pawn_structure_of(&self, color: Color, trace: Option<&mut EvalTrace>) -> TaperedScore {
+        let (own_index, enemy_index) = match color {
+            White => (WHITE_PAWN, BLACK_PAWN),
+            Black => (BLACK_PAWN, WHITE_PAWN),
+        };
+        let own = self.state.current_position[own_index].get();
+        let enemy = self.state.current_position[enemy_index].get();
+        let pawn_attacks = &self.attack_tables.pawn_attacks;
+
+        let mut doubled_score = TaperedScore::default();
+        let mut isolated_score = TaperedScore::default();
+        let mut backward_score = TaperedScore::default();
+        let mut connected_score = TaperedScore::default();
+        let mut passed_score = TaperedScore::default();
+        for sq in self.state.current_position[own_index] {
+            let file = sq % 8;
+            let rank = sq / 8;
+            let forward = FORWARD_RANKS[color as usize][rank];
+            let relative = relative_rank(sq, color);
+
+            let doubled = forward & FILE_MASKS[file] & own != 0;
+            if doubled {
+                doubled_score += DOUBLED;
+            }
+
+            if ADJACENT_FILES[file] & own == 0 {
+                isolated_score += ISOLATED;
+            } else {
+                // pawns defending it, or next to it on the same rank
+                let supported = pawn_attacks[color.opponent() as usize][sq].get() & own != 0;
+                let phalanx = ADJACENT_FILES[file] & RANK_MASKS[rank] & own != 0;
+                if supported || phalanx {
+                    connected_score += CONNECTED[relative];
+                } else {
+                    // no pawn can come to defend it, and it can't advance without being taken
+                    let behind = ADJACENT_FILES[file] & !forward & own == 0;
+                    let stop = match color {
+                        White => sq + 8,
+                        Black => sq - 8,
+                    };
+                    if behind && pawn_attacks[color as usize][stop].get() & enemy != 0 {
+                        backward_score += BACKWARD;
+                    }
+                }
+            }
+
+            // only the front pawn of a doubled pair can be passed
+            if !doubled && passed_pawn_mask(sq, color) & enemy == 0 {
+                passed_score += PASSED[relative];
+            }
+        }
+
+        if let Some(trace) = trace {
+            trace.add(EvalTerm::DoubledPawns, color, doubled_score);
+            trace.add(EvalTerm::IsolatedPawns, color, isolated_score);
+            trace.add(EvalTerm::BackwardPawns, color, backward_score);
+            trace.add(EvalTerm::ConnectedPawns, color, connected_score);
+            trace.add(EvalTerm::PassedPawns, color, passed_score);
+        }
+        doubled_score + isolated_score + backward_score + connected_score + passed_score
+    }
+}
+
+// number of entries of the pawn hash table, 1MB
+const PAWN_TABLE_ENTRIES: usize = 1 << 16;
+
+/* layout of the data word
+ *  bits  0..16 middlegame score
+ *  bits 16..32 endgame score
+ *  bit  32     set for a used entry
+ */
+const USED: u64 = 1 << 32;
+
+#[derive(Default)]
+struct PawnEntry {
+    /// pawn key xored with the data
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Pawn structure scores by pawn key. Like the transposition table it can be shared
+/// between threads without locks, since a torn entry doesn't match any key
+pub struct PawnHashTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// printing every entry is never useful
+impl fmt::Debug for PawnHashTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PawnHashTable")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl PawnHashTable {
+    pub fn new() -> Self {
+        Self {
+            entries: std::iter::repeat_with(PawnEntry::default)
+                .take(PAWN_TABLE_ENTRIES)
+                .collect(),
+        }
+    }
+
+    /// empties the table
+    pub fn clear(&self) {
+        for entry in &self.entries {
+            entry.key.store(0, Ordering::Relaxed);
+            entry.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    fn entry(&self, key: u64) -> &PawnEntry {
+        &self.entries[key as usize % PAWN_TABLE_ENTRIES]
+    }
+
+    /// score of the pawn structure with the given key, if it's in the table
+    pub fn probe(&self, key: u64) -> Option<TaperedScore> {
+        let entry = self.entry(key);
+        let data = entry.data.load(Ordering::Relaxed);
+        if data & USED == 0 || entry.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        Some(s(
+            data as u16 as i16 as Score,
+            (data >> 16) as u16 as i16 as Score,
+        ))
+    }
+
+    pub fn store(&self, key: u64, score: TaperedScore) {
+        let data = score.mg as i16 as u16 as u64 | (score.eg as i16 as u16 as u64) << 16 | USED;
+        let entry = self.entry(key);
+        entry.key.store(key ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
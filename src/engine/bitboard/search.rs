@@ -0,0 +1,799 @@
+use super::constants::*;
+use super::legality::piece_indices;
+use super::ordering::*;
+use super::transposition::*;
+use super::BitBoardEngine;
+use super::ChessEngine;
+use crate::engine::limits::*;
+use crate::engine::movelist::MoveList;
+use crate::engine::report::*;
+use crate::engine::{same_move, Score};
+use chessire_utils::color::Color::{self, Black, White};
+use chessire_utils::moves::*;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+/////***************************/////
+/////***** SEARCH CONSTANTS *****/////
+/////***************************/////
+
+// Scores are given in centipawns from the point of view of the side to move
+pub const INFINITY: Score = 50000;
+// being mated at ply n scores -MATE_SCORE + n, so shorter mates are preferred
+pub const MATE_SCORE: Score = 49000;
+
+// maximum depth the search can reach, in plies
+pub const MAX_PLY: usize = 64;
+
+// scores beyond this are mates, found within MAX_PLY plies
+pub const MATE_IN_MAX_PLY: Score = MATE_SCORE - MAX_PLY as Score;
+
+// the clock is checked every time this many nodes have been searched, must be a power of 2
+const TIME_CHECK_INTERVAL: u64 = 2048;
+
+// the move being searched at the root is only reported after this many milliseconds
+const CURRMOVE_REPORT_DELAY: u64 = 3000;
+
+// half width of the first aspiration window, doubled every time the score falls outside
+const ASPIRATION_WINDOW: Score = 25;
+// the iterations below this depth are too unstable to guess their score
+const ASPIRATION_MIN_DEPTH: usize = 4;
+
+// null move pruning is tried from this depth, and the reply is searched
+// NULL_MOVE_REDUCTION plies shallower, plus one every NULL_MOVE_DEPTH_DIVISOR
+const NULL_MOVE_MIN_DEPTH: usize = 3;
+const NULL_MOVE_REDUCTION: usize = 2;
+const NULL_MOVE_DEPTH_DIVISOR: usize = 6;
+
+// late move reductions apply from this depth, once this many moves have been searched
+const LMR_MIN_DEPTH: usize = 3;
+const LMR_MIN_MOVES: usize = 3;
+// moves after this one are all reduced the same
+const MAX_MOVES_REDUCED: usize = 64;
+
+// the static evaluation minus this margin per ply of depth left still beats beta
+const REVERSE_FUTILITY_MARGIN: Score = 120;
+const REVERSE_FUTILITY_MAX_DEPTH: usize = 6;
+
+// quiet moves can't raise the static evaluation by more than this margins,
+// indexed by the depth left
+const FUTILITY_MARGINS: [Score; 3] = [0, 200, 500];
+
+// positions this far below alpha are only searched by the quiescence search,
+// indexed by the depth left
+const RAZORING_MARGINS: [Score; 3] = [0, 300, 550];
+
+// captures that can't bring the score within this margin of alpha aren't searched
+// by the quiescence search
+const DELTA_MARGIN: Score = 200;
+
+/// Results of the last search, kept in the engine so they can be consumed later
+#[derive(Debug, Clone, Default)]
+pub struct SearchInfo {
+    /// principal variation, the first move is the best move found
+    pub pv: Vec<Move>,
+    /// score of the principal variation
+    pub score: Score,
+    /// depth of the search that produced the principal variation
+    pub depth: usize,
+    /// number of nodes visited, including the quiescence nodes
+    pub nodes: u64,
+    /// number of nodes visited by the quiescence search
+    pub qnodes: u64,
+    /// deepest ply reached
+    pub seldepth: usize,
+    //// search control
+    /// set when a limit has been reached, the search unwinds as fast as possible
+    pub stopped: bool,
+    pub start_time: Option<Instant>,
+    pub time_budget: Option<TimeBudget>,
+    pub node_limit: Option<u64>,
+    /// if not empty, only this moves are considered at the root
+    pub root_moves: Vec<Move>,
+    /// root moves skipped when searching for the next principal variation
+    pub excluded_root_moves: Vec<Move>,
+    /// true until ponderhit when the search was started in ponder mode
+    pub pondering: bool,
+    /// stop and ponderhit requests from the controlling thread
+    pub signals: Arc<SearchSignals>,
+    pub reporter: Option<SearchReporter>,
+}
+
+/// Triangular principal variation table
+/// row *ply* holds the best line found so far starting at that ply
+pub struct PvTable {
+    pub moves: Vec<[Option<Move>; MAX_PLY]>,
+    pub length: [usize; MAX_PLY],
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        Self {
+            moves: vec![[None; MAX_PLY]; MAX_PLY],
+            length: [0; MAX_PLY],
+        }
+    }
+}
+
+impl PvTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// stores mov as the best move at ply, followed by the line found at ply + 1
+    #[inline]
+    pub fn update(&mut self, ply: usize, mov: Move) {
+        self.moves[ply][ply] = Some(mov);
+        for next_ply in (ply + 1)..self.length[ply + 1] {
+            self.moves[ply][next_ply] = self.moves[ply + 1][next_ply];
+        }
+        self.length[ply] = self.length[ply + 1];
+    }
+
+    /// returns the principal variation from the root
+    pub fn line(&self) -> Vec<Move> {
+        self.moves[0][0..self.length[0]]
+            .iter()
+            .filter_map(|m| *m)
+            .collect()
+    }
+}
+
+impl BitBoardEngine {
+    /// Iterative deepening alpha-beta search within the given limits.
+    /// The principal variation and score of the last completed iteration are stored in self.search
+    pub fn search(&mut self, limits: &SearchLimits) {
+        self.search.pv.clear();
+        self.search.score = 0;
+        self.search.depth = 0;
+        self.search.nodes = 0;
+        self.search.qnodes = 0;
+        self.search.seldepth = 0;
+        self.search.stopped = false;
+        self.search.start_time = Some(Instant::now());
+        self.search.time_budget =
+            limits.time_budget(self.state.side_to_move, self.settings.move_overhead);
+        self.search.node_limit = limits.nodes;
+        self.search.root_moves = limits.searchmoves.clone();
+        self.search.pondering = limits.ponder;
+        self.search.reporter = limits.reporter;
+        self.tt.age();
+
+        let max_depth = limits
+            .max_depth()
+            .unwrap_or(MAX_PLY - 1)
+            .clamp(1, MAX_PLY - 1);
+
+        for depth in 1..=max_depth {
+            // root moves of the lines already found in this iteration
+            self.search.excluded_root_moves.clear();
+
+            for multipv in 1..=self.settings.multipv {
+                let mut pv = PvTable::new();
+                let score = self.aspiration_search(depth, multipv, &mut pv);
+
+                // the results of an interrupted search can't be trusted
+                // unless there's nothing else to play
+                if self.search.stopped && (multipv > 1 || !self.search.pv.is_empty()) {
+                    break;
+                }
+
+                let line = self.extend_pv(pv.line(), depth);
+                // every root move already has its line
+                if line.is_empty() && multipv > 1 {
+                    break;
+                }
+
+                if multipv == 1 {
+                    self.search.pv = line.clone();
+                    self.search.score = score;
+                    self.search.depth = depth;
+                }
+                if let Some(mov) = line.first() {
+                    self.search.excluded_root_moves.push(*mov);
+                }
+                self.report(SearchReport::Iteration(IterationReport {
+                    depth,
+                    multipv,
+                    seldepth: self.search.seldepth,
+                    score: reported_score(score),
+                    nodes: self.search.nodes,
+                    qnodes: self.search.qnodes,
+                    hashfull: self.tt.hashfull(),
+                    time: self.elapsed(),
+                    pv: line,
+                }));
+
+                if self.search.stopped {
+                    break;
+                }
+            }
+
+            if self.search.stopped {
+                break;
+            }
+
+            // there's no time for another iteration
+            if let Some(budget) = self.search.time_budget {
+                if !self.is_pondering() && self.elapsed() >= budget.soft.as_millis() as u64 {
+                    break;
+                }
+            }
+
+            // stop if we found the mate we were asked for
+            if let Some(mate) = limits.mate {
+                let score = self.search.score;
+                if score.abs() >= MATE_IN_MAX_PLY
+                    && (MATE_SCORE - score.abs() + 1) as usize / 2 <= mate
+                {
+                    break;
+                }
+            }
+        }
+
+        // stopped before the first iteration had a line, any move is better than none
+        if self.search.pv.is_empty() {
+            let root_moves = &self.search.root_moves;
+            let first_move = self.get_legal_moves().iter().find(|mov| {
+                root_moves.is_empty() || root_moves.iter().any(|m| same_move(*m, *mov))
+            });
+            self.search.pv.extend(first_move);
+        }
+    }
+
+    /// searches the root with a narrow window around the score of the previous iteration,
+    /// widening it until the score falls inside. Narrow windows cut off sooner
+    pub fn aspiration_search(&mut self, depth: usize, multipv: usize, pv: &mut PvTable) -> Score {
+        let previous = self.search.score;
+        // the previous score belongs to the first line, and mate scores jump between iterations
+        if depth < ASPIRATION_MIN_DEPTH || multipv > 1 || previous.abs() >= MATE_IN_MAX_PLY {
+            return self.negamax(-INFINITY, INFINITY, depth, 0, pv);
+        }
+
+        let mut delta = ASPIRATION_WINDOW;
+        let mut alpha = (previous - delta).max(-INFINITY);
+        let mut beta = (previous + delta).min(INFINITY);
+        loop {
+            let score = self.negamax(alpha, beta, depth, 0, pv);
+            if self.search.stopped {
+                return score;
+            }
+            if score <= alpha {
+                // fail low, the score can be anywhere below
+                beta = (alpha + beta) / 2;
+                alpha = (score - delta).max(-INFINITY);
+            } else if score >= beta {
+                // fail high, the score can be anywhere above
+                beta = (score + delta).min(INFINITY);
+            } else {
+                return score;
+            }
+            delta *= 2;
+        }
+    }
+
+    /// lines cut short by transposition table hits are completed with the moves stored in the table
+    fn extend_pv(&mut self, mut line: Vec<Move>, depth: usize) -> Vec<Move> {
+        let mut made = 0;
+        for mov in &line {
+            if self.make_move(*mov).is_ok() {
+                made += 1;
+            }
+        }
+        while line.len() < depth {
+            let tt_move = match self.tt.probe(self.state.hash).and_then(|entry| entry.mov) {
+                Some(tt_move) => tt_move,
+                None => break,
+            };
+            let side = self.state.side_to_move;
+            let mov = match self.get_moves(side).iter().find(|m| tt_move.matches(*m)) {
+                Some(mov) => mov,
+                None => break,
+            };
+            if self.make_move(mov).is_err() {
+                break;
+            }
+            made += 1;
+            line.push(mov);
+        }
+        for _ in 0..made {
+            self.unmake_move();
+        }
+        line
+    }
+
+    /// sends a report to the interface, if there's someone listening
+    #[inline]
+    fn report(&self, report: SearchReport) {
+        if let Some(reporter) = self.search.reporter {
+            reporter(report);
+        }
+    }
+
+    /// milliseconds since the search started
+    pub fn elapsed(&self) -> u64 {
+        match self.search.start_time {
+            Some(t) => t.elapsed().as_millis() as u64,
+            None => 0,
+        }
+    }
+
+    /// true while the search runs in ponder mode
+    /// on ponderhit the clock starts running for a normal search
+    fn is_pondering(&mut self) -> bool {
+        if self.search.pondering && !self.search.signals.ponder.load(Ordering::Relaxed) {
+            self.search.pondering = false;
+            self.search.start_time = Some(Instant::now());
+        }
+        self.search.pondering
+    }
+
+    /// checks the search limits and the stop signal,
+    /// and flags the search as stopped if one was reached
+    #[inline]
+    fn check_limits(&mut self) {
+        if let Some(limit) = self.search.node_limit {
+            if self.search.nodes >= limit {
+                self.search.stopped = true;
+            }
+        }
+        if self.search.nodes & (TIME_CHECK_INTERVAL - 1) == 0 {
+            if self.search.signals.stop.load(Ordering::Relaxed) {
+                self.search.stopped = true;
+            }
+            if let Some(budget) = self.search.time_budget {
+                if !self.is_pondering() && self.elapsed() >= budget.hard.as_millis() as u64 {
+                    self.search.stopped = true;
+                }
+            }
+        }
+    }
+
+    /// Negamax alpha-beta search, returns the score of the position for the side to move
+    pub fn negamax(
+        &mut self,
+        mut alpha: Score,
+        mut beta: Score,
+        mut depth: usize,
+        ply: usize,
+        pv: &mut PvTable,
+    ) -> Score {
+        pv.length[ply] = ply;
+
+        // don't stop searching until the check has been dealt with
+        let in_check = self.state.flags.in_check;
+        if in_check && self.settings.check_extensions {
+            depth += 1;
+        }
+
+        // resolve the captures before evaluating the position
+        if depth == 0 {
+            return self.quiescence(alpha, beta, ply);
+        }
+
+        self.search.nodes += 1;
+        self.search.seldepth = self.search.seldepth.max(ply);
+
+        self.check_limits();
+        if self.search.stopped {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return self.evaluate_relative();
+        }
+
+        if ply > 0 && self.is_draw(ply) {
+            return 0;
+        }
+
+        // mate distance pruning, even mating at the next ply can't beat a shorter mate
+        // already found, and being mated here is better than a shorter mate against us
+        if ply > 0 {
+            alpha = alpha.max(-MATE_SCORE + ply as Score);
+            beta = beta.min(MATE_SCORE - ply as Score - 1);
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        let hash = self.state.hash;
+        let mut tt_move = None;
+        if let Some(entry) = self.tt.probe(hash) {
+            tt_move = entry.mov;
+            // the root always needs a move and a principal variation
+            if ply > 0 && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score.clamp(alpha, beta),
+                    Bound::Lower if score >= beta => return beta,
+                    Bound::Upper if score <= alpha => return alpha,
+                    _ => (),
+                }
+            }
+        }
+
+        // nodes searched with a null window only have to prove a move is worse than the
+        // principal variation, so they can be pruned much more aggressively
+        let pv_node = beta - alpha > 1;
+        let mut static_eval = -INFINITY;
+        if ply > 0 && !pv_node && !in_check {
+            static_eval = self.evaluate_relative();
+            let mate_bounds = beta.abs() >= MATE_IN_MAX_PLY;
+
+            // reverse futility pruning, the position is so good that even after losing
+            // some material the score would be above beta
+            if self.settings.reverse_futility_pruning
+                && !mate_bounds
+                && depth <= REVERSE_FUTILITY_MAX_DEPTH
+                && static_eval - REVERSE_FUTILITY_MARGIN * depth as Score >= beta
+            {
+                return beta;
+            }
+
+            // razoring, the position is so bad that only a capture can save it
+            if self.settings.razoring
+                && depth < RAZORING_MARGINS.len()
+                && static_eval + RAZORING_MARGINS[depth] < alpha
+            {
+                let score = self.quiescence(alpha, beta, ply);
+                if score <= alpha {
+                    return alpha;
+                }
+            }
+
+            // null move pruning, if passing the turn is still above beta there's surely a
+            // move that is. Not in pawn endings, where having to move can be a disadvantage
+            if self.settings.null_move_pruning
+                && !mate_bounds
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && static_eval >= beta
+                && self.history.last().is_some_and(|undo| undo.mov.is_some())
+                && self.has_non_pawn_material(self.state.side_to_move)
+            {
+                let reduction = NULL_MOVE_REDUCTION + 1 + depth / NULL_MOVE_DEPTH_DIVISOR;
+                self.make_null_move();
+                let score = -self.negamax(
+                    -beta,
+                    -beta + 1,
+                    depth.saturating_sub(reduction),
+                    ply + 1,
+                    pv,
+                );
+                self.unmake_move();
+                if self.search.stopped {
+                    return 0;
+                }
+                if score >= beta {
+                    return beta;
+                }
+            }
+        }
+
+        // quiet moves at the frontier nodes can't raise the score above alpha
+        let futile = self.settings.futility_pruning
+            && ply > 0
+            && !pv_node
+            && !in_check
+            && depth < FUTILITY_MARGINS.len()
+            && alpha.abs() < MATE_IN_MAX_PLY
+            && static_eval + FUTILITY_MARGINS[depth] <= alpha;
+
+        let side = self.state.side_to_move;
+        let previous_move = self.history.last().and_then(|undo| undo.mov);
+        let mut picker = MovePicker::new(self, tt_move, ply);
+        // quiet moves searched, their history is lowered when another one causes a cutoff
+        let mut quiets_tried = MoveList::new();
+
+        // the root results are incomplete when some moves are left out
+        let store_results = ply > 0
+            || (self.search.root_moves.is_empty() && self.search.excluded_root_moves.is_empty());
+        let mut best_move = None;
+        let mut legal_moves = 0;
+        let mut moves_searched = 0;
+        while let Some(mov) = picker.next(self) {
+            legal_moves += 1;
+            if ply == 0 {
+                if (!self.search.root_moves.is_empty()
+                    && !self.search.root_moves.iter().any(|m| same_move(*m, mov)))
+                    || self
+                        .search
+                        .excluded_root_moves
+                        .iter()
+                        .any(|m| same_move(*m, mov))
+                {
+                    continue;
+                }
+                if self.elapsed() > CURRMOVE_REPORT_DELAY {
+                    self.report(SearchReport::CurrentMove {
+                        depth,
+                        mov,
+                        number: moves_searched + 1,
+                    });
+                }
+            }
+            let quiet = !is_tactical(mov);
+            if futile && quiet && moves_searched > 0 && !self.gives_check(mov) {
+                continue;
+            }
+            if quiet {
+                quiets_tried.push(mov);
+            }
+
+            self.make_legal_move(mov);
+            let gives_check = self.state.flags.in_check;
+            // principal variation search, the first move is expected to be the best one,
+            // the rest are only searched with a null window to prove they are worse
+            let score = if moves_searched == 0 {
+                -self.negamax(-beta, -alpha, depth - 1, ply + 1, pv)
+            } else {
+                // late move reductions, moves ordered late are unlikely to be good
+                // so they are searched shallower first
+                let mut reduction = 0;
+                if self.settings.late_move_reductions
+                    && depth >= LMR_MIN_DEPTH
+                    && moves_searched >= LMR_MIN_MOVES
+                    && quiet
+                    && !in_check
+                    && !gives_check
+                {
+                    reduction = lmr_reduction(depth, moves_searched);
+                    if pv_node {
+                        reduction = reduction.saturating_sub(1);
+                    }
+                    // drop into the quiescence search one ply later at most
+                    reduction = reduction.min(depth - 2);
+                }
+
+                let mut score =
+                    -self.negamax(-alpha - 1, -alpha, depth - 1 - reduction, ply + 1, pv);
+                if reduction > 0 && score > alpha {
+                    score = -self.negamax(-alpha - 1, -alpha, depth - 1, ply + 1, pv);
+                }
+                if score > alpha && score < beta {
+                    score = -self.negamax(-beta, -alpha, depth - 1, ply + 1, pv);
+                }
+                score
+            };
+            self.unmake_move();
+            moves_searched += 1;
+
+            // the root keeps going so there's always a move to play
+            if self.search.stopped && ply > 0 {
+                return 0;
+            }
+
+            // fail hard beta cutoff
+            if score >= beta {
+                if !is_tactical(mov) && !self.search.stopped {
+                    self.ordering.update_quiet_stats(
+                        side,
+                        mov,
+                        &quiets_tried,
+                        previous_move,
+                        ply,
+                        depth,
+                    );
+                }
+                if store_results && !self.search.stopped {
+                    self.tt
+                        .store(hash, depth, Bound::Lower, score_to_tt(beta, ply), Some(mov));
+                }
+                return beta;
+            }
+            // found a better move
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mov);
+                pv.update(ply, mov);
+            }
+        }
+
+        // no legal moves, either checkmate or stalemate
+        if legal_moves == 0 {
+            return if in_check {
+                // prefer the shortest mates
+                -MATE_SCORE + ply as Score
+            } else {
+                0
+            };
+        }
+
+        if store_results && !self.search.stopped {
+            let bound = if best_move.is_some() {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt
+                .store(hash, depth, bound, score_to_tt(alpha, ply), best_move);
+        }
+        alpha
+    }
+
+    /// Quiescence search, only captures and promotions are searched so the position
+    /// is evaluated once it's quiet. When in check every evasion is searched instead
+    pub fn quiescence(&mut self, mut alpha: Score, beta: Score, ply: usize) -> Score {
+        self.search.nodes += 1;
+        self.search.qnodes += 1;
+        self.search.seldepth = self.search.seldepth.max(ply);
+
+        self.check_limits();
+        if self.search.stopped {
+            return 0;
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return self.evaluate_relative();
+        }
+
+        let in_check = self.state.flags.in_check;
+        let mut stand_pat = -INFINITY;
+        let mut move_list = if in_check {
+            let evasions = self.get_evasions();
+            if evasions.is_empty() {
+                return -MATE_SCORE + ply as Score;
+            }
+            evasions
+        } else {
+            // the side to move can usually do at least as well as the static evaluation,
+            // so it's a lower bound of the score
+            stand_pat = self.evaluate_relative();
+            if stand_pat >= beta {
+                return beta;
+            }
+            // delta pruning, not even winning a queen would raise alpha
+            if stand_pat + self.max_capture_gain() + DELTA_MARGIN < alpha {
+                return alpha;
+            }
+            alpha = alpha.max(stand_pat);
+            self.get_captures()
+        };
+
+        move_list.score_moves(|mov| mvv_lva(self, mov));
+
+        let mut index = 0;
+        while let Some(mov) = move_list.pick_best(index) {
+            index += 1;
+            if !in_check {
+                // skip the captures that can't raise alpha
+                if mov.promoted_piece.is_none() {
+                    let victim = self.captured_piece(mov).map_or(0, piece_value);
+                    if stand_pat + victim + DELTA_MARGIN < alpha {
+                        continue;
+                    }
+                }
+                // and the ones that lose material
+                if !self.see_ge(mov, 0) {
+                    continue;
+                }
+            }
+
+            self.make_legal_move(mov);
+            let score = -self.quiescence(-beta, -alpha, ply + 1);
+            self.unmake_move();
+
+            if self.search.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// the most material the side to move can win with a single move
+    fn max_capture_gain(&self) -> Score {
+        let (pawn, ..) = piece_indices(self.state.side_to_move);
+        let seventh_rank = match self.state.side_to_move {
+            White => RANK_7,
+            Black => RANK_2,
+        };
+        let mut gain = piece_value(WHITE_QUEEN);
+        // a pawn about to promote can capture and become a queen in the same move
+        if self.state.current_position[pawn].get() & seventh_rank != 0 {
+            gain += piece_value(WHITE_QUEEN) - piece_value(WHITE_PAWN);
+        }
+        gain
+    }
+
+    /// true if the position is drawn by repetition, the fifty move rule or lack of material
+    #[inline]
+    fn is_draw(&self, ply: usize) -> bool {
+        self.is_repetition(ply)
+            || self.is_insufficient_material()
+            // unless the last move was a mate
+            || (self.is_fifty_moves_draw()
+                && !(self.state.flags.in_check && self.get_evasions().is_empty()))
+    }
+
+    /// true if side has any piece other than the king and pawns
+    #[inline]
+    fn has_non_pawn_material(&self, side: Color) -> bool {
+        let (pawn, .., king) = piece_indices(side);
+        let pieces = &self.state.current_position;
+        self.state.occupancies[side as usize].get() & !(pieces[pawn].get() | pieces[king].get())
+            != 0
+    }
+
+    /// static evaluation from the point of view of the side to move
+    #[inline]
+    pub fn evaluate_relative(&self) -> Score {
+        let score = self.evaluate();
+        match self.state.side_to_move {
+            White => score,
+            Black => -score,
+        }
+    }
+
+    /// true if the king of side is attacked
+    #[inline]
+    pub fn is_in_check(&self, side: Color) -> bool {
+        if side == self.state.side_to_move {
+            return self.state.flags.in_check;
+        }
+        let king = match side {
+            White => WHITE_KING,
+            Black => BLACK_KING,
+        };
+        match self.state.current_position[king].get_lsb() {
+            Some(sq) => self.is_square_attacked_by(sq, side.opponent()),
+            None => false,
+        }
+    }
+}
+
+/// plies a late move is reduced by, growing with the depth left and the number of moves
+/// already searched. The table is computed the first time it's needed
+fn lmr_reduction(depth: usize, moves_searched: usize) -> usize {
+    static TABLE: OnceLock<[[usize; MAX_MOVES_REDUCED]; MAX_PLY]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [[0; MAX_MOVES_REDUCED]; MAX_PLY];
+        for (depth, row) in table.iter_mut().enumerate().skip(1) {
+            for (moves, reduction) in row.iter_mut().enumerate().skip(1) {
+                *reduction = (0.75 + (depth as f64).ln() * (moves as f64).ln() / 2.25) as usize;
+            }
+        }
+        table
+    });
+    table[depth.min(MAX_PLY - 1)][moves_searched.min(MAX_MOVES_REDUCED - 1)]
+}
+
+/// mate scores are stored in the transposition table as the distance to the mate from the
+/// position stored, instead of from the root, so they stay right when reached at another ply
+#[inline]
+pub fn score_to_tt(score: Score, ply: usize) -> Score {
+    if score >= MATE_IN_MAX_PLY {
+        score + ply as Score
+    } else if score <= -MATE_IN_MAX_PLY {
+        score - ply as Score
+    } else {
+        score
+    }
+}
+
+/// inverse of score_to_tt, the distance to the mate is taken from the root again
+#[inline]
+pub fn score_from_tt(score: Score, ply: usize) -> Score {
+    if score >= MATE_IN_MAX_PLY {
+        score - ply as Score
+    } else if score <= -MATE_IN_MAX_PLY {
+        score + ply as Score
+    } else {
+        score
+    }
+}
+
+/// converts a search score to centipawns or moves to mate
+pub fn reported_score(score: Score) -> ReportedScore {
+    if score >= MATE_IN_MAX_PLY {
+        ReportedScore::Mate((MATE_SCORE - score + 1) / 2)
+    } else if score <= -MATE_IN_MAX_PLY {
+        ReportedScore::Mate(-(MATE_SCORE + score) / 2)
+    } else {
+        ReportedScore::Centipawns(score)
+    }
+}
@@ -0,0 +1,176 @@
+use super::attacks::*;
+use super::constants::*;
+use super::legality::piece_indices;
+use super::{enpassant_victim, BitBoard, BitBoardEngine};
+use crate::engine::Score;
+use chessire_utils::color::Color;
+use chessire_utils::moves::Move;
+
+/* Static exchange evaluation, see the chessprogramming wiki for details.
+ * Works out the material won or lost by a capture if both sides keep recapturing on the
+ * target square with their least valuable piece, and each side can stop recapturing when
+ * it isn't profitable anymore. The pieces that capture are removed from the occupancy, so
+ * the sliders behind them (x-rays) join the exchange. Pins and checks are ignored.
+ */
+
+// the king is worth more than anything it can capture, so it recaptures last
+// and never into a defended square
+const SEE_VALUES: [Score; 6] = [
+    SEE_PAWN_VALUE,
+    SEE_KNIGHT_VALUE,
+    SEE_BISHOP_VALUE,
+    SEE_ROOK_VALUE,
+    SEE_QUEEN_VALUE,
+    SEE_KING_VALUE,
+];
+
+// an exchange can't last longer than the number of pieces on the board
+const MAX_EXCHANGES: usize = 32;
+
+#[inline]
+fn see_value(index: usize) -> Score {
+    SEE_VALUES[index % 6]
+}
+
+impl BitBoardEngine {
+    /// material won by mov after every profitable recapture, in centipawns
+    /// from the point of view of the side making the move
+    pub fn see(&self, mov: Move) -> Score {
+        let (gain, mut victim, mut occupancy) = self.see_start(mov);
+        let target = mov.target.to_usize();
+        let mut side = mov.piece.get_color();
+        let mut attackers = self.attackers_to(target, occupancy);
+
+        // gains[i] is the material won by the side that makes the i-th capture, if the
+        // exchange stopped there
+        let mut gains = [0; MAX_EXCHANGES];
+        gains[0] = gain;
+        let mut depth = 0;
+        while depth + 1 < MAX_EXCHANGES {
+            side = side.opponent();
+            let (index, sq) = match self.least_valuable_attacker(attackers, side) {
+                Some(attacker) => attacker,
+                None => break,
+            };
+            depth += 1;
+            gains[depth] = victim - gains[depth - 1];
+            victim = see_value(index);
+            occupancy.reset_bit(sq);
+            attackers = self.attackers_to(target, occupancy);
+        }
+
+        // each side only recaptures if it's better than stopping
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// true if the static exchange evaluation of mov is at least threshold,
+    /// it stops as soon as the result is known so it's cheaper than see
+    pub fn see_ge(&self, mov: Move, threshold: Score) -> bool {
+        let (gain, victim, mut occupancy) = self.see_start(mov);
+        let target = mov.target.to_usize();
+
+        // even if the moving piece is lost for free
+        let mut balance = gain - threshold;
+        if balance < 0 {
+            return false;
+        }
+        balance = victim - balance;
+        if balance <= 0 {
+            return true;
+        }
+
+        let mut side = mov.piece.get_color();
+        let mut attackers = self.attackers_to(target, occupancy);
+        // true while the side that made the move is ahead of the threshold
+        let mut result = true;
+        loop {
+            side = side.opponent();
+            let (index, sq) = match self.least_valuable_attacker(attackers, side) {
+                Some(attacker) => attacker,
+                None => break,
+            };
+            result = !result;
+
+            // the king can only recapture if the square isn't defended anymore
+            if index % 6 == WHITE_KING {
+                occupancy.reset_bit(sq);
+                let defenders = self.attackers_to(target, occupancy).get()
+                    & self.state.occupancies[side.opponent() as usize].get();
+                return if defenders != 0 { !result } else { result };
+            }
+
+            balance = see_value(index) - balance;
+            if balance < result as Score {
+                break;
+            }
+            occupancy.reset_bit(sq);
+            attackers = self.attackers_to(target, occupancy);
+        }
+        result
+    }
+
+    /// material won by the capture, value of the piece left on the target square
+    /// and occupancy once the move has been made
+    fn see_start(&self, mov: Move) -> (Score, Score, BitBoard) {
+        let mut occupancy = self.state.occupancies[BOTH];
+        occupancy.reset_bit(mov.source.to_usize());
+
+        let mut gain = self.captured_piece(mov).map_or(0, see_value);
+        if mov.enpassant {
+            // the captured pawn isn't on the target square, its file opens up
+            occupancy.reset_bit(enpassant_victim(mov));
+        }
+        let victim = match mov.promoted_piece {
+            Some(piece) => {
+                let promoted = see_value(get_bb_piece_index(piece));
+                gain += promoted - see_value(WHITE_PAWN);
+                promoted
+            }
+            None => see_value(get_bb_piece_index(mov.piece)),
+        };
+        (gain, victim, occupancy)
+    }
+
+    /// pieces of both colors attacking sq, with the given occupancy
+    pub fn attackers_to(&self, sq: usize, occupancy: BitBoard) -> BitBoard {
+        let pieces = &self.state.current_position;
+        let tables = &self.attack_tables;
+        let bishops = pieces[WHITE_BISHOP].get()
+            | pieces[BLACK_BISHOP].get()
+            | pieces[WHITE_QUEEN].get()
+            | pieces[BLACK_QUEEN].get();
+        let rooks = pieces[WHITE_ROOK].get()
+            | pieces[BLACK_ROOK].get()
+            | pieces[WHITE_QUEEN].get()
+            | pieces[BLACK_QUEEN].get();
+
+        // pawns attack sq if a pawn of the other color on sq would attack them
+        let attackers = (tables.pawn_attacks[Color::Black as usize][sq].get()
+            & pieces[WHITE_PAWN].get())
+            | (tables.pawn_attacks[Color::White as usize][sq].get() & pieces[BLACK_PAWN].get())
+            | (tables.knight_attacks[sq].get()
+                & (pieces[WHITE_KNIGHT].get() | pieces[BLACK_KNIGHT].get()))
+            | (tables.king_attacks[sq].get()
+                & (pieces[WHITE_KING].get() | pieces[BLACK_KING].get()))
+            | (get_bishop_attack(tables, sq, occupancy).get() & bishops)
+            | (get_rook_attack(tables, sq, occupancy).get() & rooks);
+        // pieces that already captured are gone
+        BitBoard::new(attackers & occupancy.get())
+    }
+
+    /// bitboard index and square of the least valuable piece of side among attackers
+    fn least_valuable_attacker(&self, attackers: BitBoard, side: Color) -> Option<(usize, usize)> {
+        let (pawn, knight, bishop, rook, queen, king) = piece_indices(side);
+        [pawn, knight, bishop, rook, queen, king]
+            .iter()
+            .find_map(|&index| {
+                (attackers & self.state.current_position[index])
+                    .get_lsb()
+                    .map(|sq| (index, sq))
+            })
+    }
+}
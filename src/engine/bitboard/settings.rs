@@ -0,0 +1,161 @@
+use super::evaluation::{EG_PIECE_VALUES, MG_PIECE_VALUES};
+use crate::engine::options::*;
+
+// default values
+
+pub const DEFAULT_HASH_MB: usize = 128;
+pub const MAX_HASH_MB: usize = 1024 * 1024;
+pub const DEFAULT_THREADS: usize = 1;
+// the search is single threaded for now
+pub const MAX_THREADS: usize = 1;
+pub const DEFAULT_MULTIPV: usize = 1;
+pub const MAX_MULTIPV: usize = 256;
+pub const DEFAULT_MOVE_OVERHEAD: u64 = 30;
+pub const MAX_MOVE_OVERHEAD: u64 = 5000;
+pub const MAX_PIECE_VALUE: i64 = 5000;
+
+// option names, as shown to the GUI
+
+pub const HASH: &str = "Hash";
+pub const THREADS: &str = "Threads";
+pub const MULTIPV: &str = "MultiPV";
+pub const PONDER: &str = "Ponder";
+pub const CLEAR_HASH: &str = "Clear Hash";
+pub const MOVE_OVERHEAD: &str = "Move Overhead";
+// search features, they can be turned off to measure what they're worth
+pub const NULL_MOVE_PRUNING: &str = "Null Move Pruning";
+pub const LATE_MOVE_REDUCTIONS: &str = "Late Move Reductions";
+pub const REVERSE_FUTILITY_PRUNING: &str = "Reverse Futility Pruning";
+pub const FUTILITY_PRUNING: &str = "Futility Pruning";
+pub const RAZORING: &str = "Razoring";
+pub const CHECK_EXTENSIONS: &str = "Check Extensions";
+// evaluation parameters, see params.rs
+pub const EVAL_FILE: &str = "Eval File";
+pub const EVAL_PARAMS: &str = "Eval Params";
+pub const SAVE_EVAL_FILE: &str = "Save Eval File";
+/// material value options, middlegame and endgame for each piece but the king
+pub const PIECE_VALUE_OPTIONS: [&str; 10] = [
+    "Pawn Value Mg",
+    "Pawn Value Eg",
+    "Knight Value Mg",
+    "Knight Value Eg",
+    "Bishop Value Mg",
+    "Bishop Value Eg",
+    "Rook Value Mg",
+    "Rook Value Eg",
+    "Queen Value Mg",
+    "Queen Value Eg",
+];
+
+const fn piece_value_option(index: usize) -> EngineOption {
+    let values = [MG_PIECE_VALUES, EG_PIECE_VALUES][index % 2];
+    EngineOption::new(
+        PIECE_VALUE_OPTIONS[index],
+        OptionType::Spin {
+            default: values[index / 2] as i64,
+            min: 0,
+            max: MAX_PIECE_VALUE,
+        },
+    )
+}
+
+/// Options the bitboard engine exposes through setoption
+pub const ENGINE_OPTIONS: [EngineOption; 25] = [
+    EngineOption::new(
+        HASH,
+        OptionType::Spin {
+            default: DEFAULT_HASH_MB as i64,
+            min: 1,
+            max: MAX_HASH_MB as i64,
+        },
+    ),
+    EngineOption::new(
+        THREADS,
+        OptionType::Spin {
+            default: DEFAULT_THREADS as i64,
+            min: 1,
+            max: MAX_THREADS as i64,
+        },
+    ),
+    EngineOption::new(
+        MULTIPV,
+        OptionType::Spin {
+            default: DEFAULT_MULTIPV as i64,
+            min: 1,
+            max: MAX_MULTIPV as i64,
+        },
+    ),
+    EngineOption::new(PONDER, OptionType::Check { default: false }),
+    EngineOption::new(CLEAR_HASH, OptionType::Button),
+    EngineOption::new(
+        MOVE_OVERHEAD,
+        OptionType::Spin {
+            default: DEFAULT_MOVE_OVERHEAD as i64,
+            min: 0,
+            max: MAX_MOVE_OVERHEAD as i64,
+        },
+    ),
+    EngineOption::new(NULL_MOVE_PRUNING, OptionType::Check { default: true }),
+    EngineOption::new(LATE_MOVE_REDUCTIONS, OptionType::Check { default: true }),
+    EngineOption::new(
+        REVERSE_FUTILITY_PRUNING,
+        OptionType::Check { default: true },
+    ),
+    EngineOption::new(FUTILITY_PRUNING, OptionType::Check { default: true }),
+    EngineOption::new(RAZORING, OptionType::Check { default: true }),
+    EngineOption::new(CHECK_EXTENSIONS, OptionType::Check { default: true }),
+    // an empty file restores the default parameters
+    EngineOption::new(EVAL_FILE, OptionType::String { default: "" }),
+    EngineOption::new(EVAL_PARAMS, OptionType::String { default: "" }),
+    EngineOption::new(SAVE_EVAL_FILE, OptionType::String { default: "" }),
+    piece_value_option(0),
+    piece_value_option(1),
+    piece_value_option(2),
+    piece_value_option(3),
+    piece_value_option(4),
+    piece_value_option(5),
+    piece_value_option(6),
+    piece_value_option(7),
+    piece_value_option(8),
+    piece_value_option(9),
+];
+
+/// Current value of the engine options
+#[derive(Debug, Clone, Copy)]
+pub struct EngineSettings {
+    /// size of the transposition table in MB
+    pub hash_mb: usize,
+    /// number of search threads
+    pub threads: usize,
+    /// number of principal variations searched and reported
+    pub multipv: usize,
+    /// the GUI allows the engine to ponder
+    pub ponder: bool,
+    /// milliseconds kept in reserve for the communication with the GUI
+    pub move_overhead: u64,
+    //// search features
+    pub null_move_pruning: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility_pruning: bool,
+    pub futility_pruning: bool,
+    pub razoring: bool,
+    pub check_extensions: bool,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            hash_mb: DEFAULT_HASH_MB,
+            threads: DEFAULT_THREADS,
+            multipv: DEFAULT_MULTIPV,
+            ponder: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            null_move_pruning: true,
+            late_move_reductions: true,
+            reverse_futility_pruning: true,
+            futility_pruning: true,
+            razoring: true,
+            check_extensions: true,
+        }
+    }
+}
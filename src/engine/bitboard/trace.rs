@@ -0,0 +1,162 @@
+use super::evaluation::*;
+use super::BitBoardEngine;
+use crate::engine::Score;
+use chessire_utils::color::Color::{self, Black, White};
+use std::fmt;
+
+/* Evaluation trace.
+ * Breaks the evaluation down by term and color, to see why the engine likes a position.
+ * The trace is filled by evaluating the position again with tracing enabled, so the search
+ * doesn't pay for it. make_move keeps the material and the piece square tables as a single
+ * score, they are split again when computing it from scratch.
+ */
+
+/// Terms of the evaluation, as shown by the eval command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalTerm {
+    Material,
+    PieceSquares,
+    DoubledPawns,
+    IsolatedPawns,
+    BackwardPawns,
+    ConnectedPawns,
+    PassedPawns,
+    Mobility,
+    KingAttacks,
+    Pieces,
+    PawnShield,
+    KingFiles,
+}
+
+impl EvalTerm {
+    pub const ALL: [EvalTerm; 12] = [
+        EvalTerm::Material,
+        EvalTerm::PieceSquares,
+        EvalTerm::DoubledPawns,
+        EvalTerm::IsolatedPawns,
+        EvalTerm::BackwardPawns,
+        EvalTerm::ConnectedPawns,
+        EvalTerm::PassedPawns,
+        EvalTerm::Mobility,
+        EvalTerm::KingAttacks,
+        EvalTerm::Pieces,
+        EvalTerm::PawnShield,
+        EvalTerm::KingFiles,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EvalTerm::Material => "Material",
+            EvalTerm::PieceSquares => "Piece squares",
+            EvalTerm::DoubledPawns => "Doubled pawns",
+            EvalTerm::IsolatedPawns => "Isolated pawns",
+            EvalTerm::BackwardPawns => "Backward pawns",
+            EvalTerm::ConnectedPawns => "Connected pawns",
+            EvalTerm::PassedPawns => "Passed pawns",
+            EvalTerm::Mobility => "Mobility",
+            EvalTerm::KingAttacks => "King attacks",
+            EvalTerm::Pieces => "Pieces",
+            EvalTerm::PawnShield => "Pawn shield",
+            EvalTerm::KingFiles => "King files",
+        }
+    }
+}
+
+/// Score of every term of the evaluation for each color
+#[derive(Debug, Default, Clone)]
+pub struct EvalTrace {
+    /// indexed by term and color
+    pub terms: [[TaperedScore; 2]; EvalTerm::ALL.len()],
+    pub phase: i32,
+}
+
+impl EvalTrace {
+    #[inline]
+    pub fn add(&mut self, term: EvalTerm, color: Color, score: TaperedScore) {
+        self.terms[term as usize][color as usize] += score;
+    }
+
+    /// score of term from the point of view of white
+    pub fn term_total(&self, term: EvalTerm) -> TaperedScore {
+        let scores = &self.terms[term as usize];
+        scores[White as usize] - scores[Black as usize]
+    }
+
+    /// sum of all the terms from the point of view of white
+    pub fn total(&self) -> TaperedScore {
+        EvalTerm::ALL
+            .iter()
+            .fold(TaperedScore::default(), |total, term| {
+                total + self.term_total(*term)
+            })
+    }
+
+    /// the evaluation of the position, in centipawns from the point of view of white
+    pub fn score(&self) -> Score {
+        self.total().taper(self.phase)
+    }
+
+    fn write_scores(&self, f: &mut fmt::Formatter<'_>, score: TaperedScore) -> fmt::Result {
+        write!(
+            f,
+            " {:>6} {:>6} {:>6} |",
+            score.mg,
+            score.eg,
+            score.taper(self.phase)
+        )
+    }
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator =
+            "----------------+----------------------+----------------------+----------------------+";
+        writeln!(
+            f,
+            "           Term |        White         |        Black         |        Total         |"
+        )?;
+        writeln!(
+            f,
+            "                |     mg     eg  total |     mg     eg  total |     mg     eg  total |"
+        )?;
+        writeln!(f, "{}", separator)?;
+        for term in EvalTerm::ALL.iter().copied() {
+            write!(f, "{:>15} |", term.name())?;
+            let scores = &self.terms[term as usize];
+            self.write_scores(f, scores[White as usize])?;
+            self.write_scores(f, scores[Black as usize])?;
+            self.write_scores(f, self.term_total(term))?;
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", separator)?;
+        write!(f, "{:>15} |{:>22}|{:>22}|", "Total", "", "")?;
+        self.write_scores(f, self.total())?;
+        writeln!(f)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Game phase: {} of {}",
+            self.phase.min(MAX_PHASE),
+            MAX_PHASE
+        )?;
+        write!(f, "Evaluation: {} (white side)", self.score())
+    }
+}
+
+impl BitBoardEngine {
+    /// evaluates the position term by term
+    pub fn trace_eval(&self) -> EvalTrace {
+        let mut trace = EvalTrace {
+            phase: self.state.phase,
+            ..Default::default()
+        };
+        self.state.compute_psqt(&self.eval_params, Some(&mut trace));
+        for color in [White, Black].iter().copied() {
+            self.pawn_structure_of(color, Some(&mut trace));
+            self.piece_activity_of(color, Some(&mut trace));
+            self.king_safety_of(color, Some(&mut trace));
+        }
+        debug_assert_eq!(trace.score(), self.tapered_eval());
+        trace
+    }
+}
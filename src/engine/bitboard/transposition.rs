@@ -0,0 +1,283 @@
+use super::constants::*;
+use crate::engine::Score;
+use chessire_utils::moves::Move;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+/* Transposition table, see the chessprogramming wiki for details.
+ * The table is split in buckets of ENTRIES_PER_BUCKET entries that fill a cache line, the
+ * position key selects the bucket and any entry of the bucket can hold the position.
+ * Entries are two atomic words, the key is stored xored with the data so an entry torn by
+ * a concurrent write doesn't match any position, and the table can be shared between
+ * threads without locks.
+ */
+
+const ENTRIES_PER_BUCKET: usize = 4;
+
+// the age is stored in 6 bits, and wraps around
+const AGE_MASK: u8 = 0x3F;
+
+// entries sampled to compute hashfull
+const HASHFULL_SAMPLE: usize = 1000;
+
+/// Kind of score stored in an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// the score is exact, it was inside the search window
+    Exact,
+    /// fail high, the score is at least this value
+    Lower,
+    /// fail low, the score is at most this value
+    Upper,
+}
+
+/// Best move of an entry, packed as source, target and promoted piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtMove(u16);
+
+impl TtMove {
+    pub fn new(mov: Move) -> Self {
+        let promoted = match mov.promoted_piece {
+            Some(piece) => get_bb_piece_index(piece) as u16 + 1,
+            None => 0,
+        };
+        Self(mov.source.to_usize() as u16 | (mov.target.to_usize() as u16) << 6 | promoted << 12)
+    }
+
+    /// true if mov is the move stored
+    #[inline]
+    pub fn matches(&self, mov: Move) -> bool {
+        *self == TtMove::new(mov)
+    }
+
+    /// square the move starts from
+    #[inline]
+    pub fn source(&self) -> usize {
+        (self.0 & 0x3F) as usize
+    }
+}
+
+/// Contents of an entry, as returned by a probe
+#[derive(Debug, Clone, Copy)]
+pub struct TtEntry {
+    pub depth: usize,
+    pub bound: Bound,
+    pub score: Score,
+    pub mov: Option<TtMove>,
+}
+
+/* layout of the data word
+ *  bits  0..16 best move, 0 if there's none
+ *  bits 16..48 score
+ *  bits 48..56 depth
+ *  bits 56..58 bound, 0 for an empty entry
+ *  bits 58..64 age
+ */
+impl TtEntry {
+    fn pack(&self, age: u8) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        self.mov.map_or(0, |m| m.0 as u64)
+            | (self.score as u32 as u64) << 16
+            | (self.depth.min(u8::MAX as usize) as u64) << 48
+            | bound << 56
+            | ((age & AGE_MASK) as u64) << 58
+    }
+
+    fn unpack(data: u64) -> Option<Self> {
+        let bound = match (data >> 56) & 0x3 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let mov = (data & 0xFFFF) as u16;
+        Some(Self {
+            depth: ((data >> 48) & 0xFF) as usize,
+            bound,
+            score: (data >> 16) as u32 as Score,
+            mov: if mov == 0 { None } else { Some(TtMove(mov)) },
+        })
+    }
+}
+
+#[inline]
+fn data_age(data: u64) -> u8 {
+    (data >> 58) as u8 & AGE_MASK
+}
+
+#[inline]
+fn data_depth(data: u64) -> usize {
+    ((data >> 48) & 0xFF) as usize
+}
+
+#[derive(Default)]
+struct Entry {
+    /// position key xored with the data
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Entry {
+    #[inline]
+    fn load(&self) -> (u64, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        (self.key.load(Ordering::Relaxed) ^ data, data)
+    }
+
+    #[inline]
+    fn save(&self, key: u64, data: u64) {
+        self.key.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+#[repr(align(64))]
+struct Bucket {
+    entries: [Entry; ENTRIES_PER_BUCKET],
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    /// age of the current search, entries left by older searches are replaced first
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// creates a table using size_mb megabytes, fails if the memory can't be allocated
+    pub fn new(size_mb: usize) -> Result<Self, String> {
+        let len = (size_mb * 1024 * 1024 / std::mem::size_of::<Bucket>()).max(1);
+        let mut buckets = Vec::new();
+        buckets
+            .try_reserve_exact(len)
+            .map_err(|_| format!("unable to allocate {} MB for the hash table", size_mb))?;
+        buckets.extend(std::iter::repeat_with(Bucket::default).take(len));
+        Ok(Self {
+            buckets,
+            age: AtomicU8::new(0),
+        })
+    }
+
+    /// empties the table
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            for entry in &bucket.entries {
+                entry.save(0, 0);
+            }
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// makes the entries of the previous searches older, so they are replaced first
+    pub fn age(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age
+            .store(age.wrapping_add(1) & AGE_MASK, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn bucket(&self, key: u64) -> &Bucket {
+        // maps the key to 0..len without a division
+        let index = ((key as u128 * self.buckets.len() as u128) >> 64) as usize;
+        &self.buckets[index]
+    }
+
+    /// looks for the position with the given key
+    pub fn probe(&self, key: u64) -> Option<TtEntry> {
+        self.bucket(key)
+            .entries
+            .iter()
+            .map(|entry| entry.load())
+            .find(|(entry_key, _)| *entry_key == key)
+            .and_then(|(_, data)| TtEntry::unpack(data))
+    }
+
+    /// stores the result of a search of the position with the given key
+    pub fn store(&self, key: u64, depth: usize, bound: Bound, score: Score, mov: Option<Move>) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = self.bucket(key);
+        let mut mov = mov.map(TtMove::new);
+
+        // the position is already in the table
+        if let Some(entry) = bucket.entries.iter().find(|e| e.load().0 == key) {
+            let (_, data) = entry.load();
+            if let Some(old) = TtEntry::unpack(data) {
+                // keep deeper results of the current search, unless they aren't exact
+                if bound != Bound::Exact && data_age(data) == age && old.depth > depth + 2 {
+                    return;
+                }
+                // don't forget the best move if we don't have a new one
+                mov = mov.or(old.mov);
+            }
+            entry.save(
+                key,
+                TtEntry {
+                    depth,
+                    bound,
+                    score,
+                    mov,
+                }
+                .pack(age),
+            );
+            return;
+        }
+
+        // otherwise replace the least valuable entry: empty, old or shallow, in that order
+        let victim = bucket
+            .entries
+            .iter()
+            .min_by_key(|e| {
+                let (_, data) = e.load();
+                if TtEntry::unpack(data).is_none() {
+                    return i32::MIN;
+                }
+                let relative_age = (age.wrapping_sub(data_age(data)) & AGE_MASK) as i32;
+                data_depth(data) as i32 - 8 * relative_age
+            })
+            .unwrap();
+        victim.save(
+            key,
+            TtEntry {
+                depth,
+                bound,
+                score,
+                mov,
+            }
+            .pack(age),
+        );
+    }
+
+    /// permill of the table used by the current search, as reported by the uci hashfull info
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let sampled = self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.entries.iter())
+            .take(HASHFULL_SAMPLE);
+        let mut total = 0;
+        let mut used = 0;
+        for entry in sampled {
+            let (_, data) = entry.load();
+            if TtEntry::unpack(data).is_some() && data_age(data) == age {
+                used += 1;
+            }
+            total += 1;
+        }
+        used * 1000 / total
+    }
+}
+
+// printing every entry is never useful
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("buckets", &self.buckets.len())
+            .field("age", &self.age.load(Ordering::Relaxed))
+            .finish()
+    }
+}
@@ -0,0 +1,104 @@
+use crate::castling::CastlingRights;
+
+/* Zobrist hashing, see the chessprogramming wiki for details.
+ * Every feature of the position (a piece on a square, the side to move, the castling rights
+ * and the en passant file) gets a random key, and the hash of the position is the xor of the
+ * keys of its features. Making a move only requires xoring in and out the features that changed.
+ */
+
+pub struct ZobristKeys {
+    /// one key per bitboard index and square
+    pub pieces: [[u64; 64]; 12],
+    /// xored when black is to move
+    pub side: u64,
+    /// one key per combination of castling rights
+    pub castling: [u64; 16],
+    /// one key per en passant file
+    pub enpassant: [u64; 8],
+}
+
+// the keys are generated at compile time, so they're the same on every run
+pub static ZOBRIST_KEYS: ZobristKeys = ZobristKeys::generate();
+
+// seed for the pseudo random number generator
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// xorshift64* pseudo random number generator, usable in const context
+/// returns the next state of the generator
+const fn next_state(state: u64) -> u64 {
+    let mut x = state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    x
+}
+
+/// random number for the given generator state
+const fn random_from_state(state: u64) -> u64 {
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+impl ZobristKeys {
+    const fn generate() -> Self {
+        let mut keys = Self {
+            pieces: [[0; 64]; 12],
+            side: 0,
+            castling: [0; 16],
+            enpassant: [0; 8],
+        };
+        let mut state = SEED;
+
+        let mut piece = 0;
+        while piece < 12 {
+            let mut sq = 0;
+            while sq < 64 {
+                state = next_state(state);
+                keys.pieces[piece][sq] = random_from_state(state);
+                sq += 1;
+            }
+            piece += 1;
+        }
+
+        state = next_state(state);
+        keys.side = random_from_state(state);
+
+        let mut i = 0;
+        while i < 16 {
+            state = next_state(state);
+            keys.castling[i] = random_from_state(state);
+            i += 1;
+        }
+
+        let mut file = 0;
+        while file < 8 {
+            state = next_state(state);
+            keys.enpassant[file] = random_from_state(state);
+            file += 1;
+        }
+        keys
+    }
+}
+
+/// index of the castling rights in the castling keys table
+#[inline]
+pub fn castling_index(cr: CastlingRights) -> usize {
+    (cr.white_king_side as usize)
+        | (cr.white_queen_side as usize) << 1
+        | (cr.black_king_side as usize) << 2
+        | (cr.black_queen_side as usize) << 3
+}
+
+#[inline]
+pub fn piece_key(piece: usize, sq: usize) -> u64 {
+    ZOBRIST_KEYS.pieces[piece][sq]
+}
+
+#[inline]
+pub fn castling_key(cr: CastlingRights) -> u64 {
+    ZOBRIST_KEYS.castling[castling_index(cr)]
+}
+
+#[inline]
+pub fn enpassant_key(sq: usize) -> u64 {
+    ZOBRIST_KEYS.enpassant[sq % 8]
+}
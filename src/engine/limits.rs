@@ -0,0 +1,147 @@
+use super::report::SearchReporter;
+use chessire_utils::color::Color;
+use chessire_utils::moves::Move;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+// moves left in the game assumed when the GUI doesn't send movestogo
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+// how many times the soft limit the engine can spend on a single move
+const HARD_LIMIT_FACTOR: u64 = 3;
+// time for a move when the GUI only sent the clock of the other side
+const MISSING_CLOCK_MOVE_TIME: u64 = 1000;
+
+/// Limits for a search, as sent by the UCI go command
+/// all times are given in milliseconds
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    /// restrict the search to this moves only
+    pub searchmoves: Vec<Move>,
+    /// search in ponder mode
+    pub ponder: bool,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    /// moves left until the next time control
+    pub movestogo: Option<u64>,
+    /// maximum depth in plies
+    pub depth: Option<usize>,
+    /// maximum number of nodes
+    pub nodes: Option<u64>,
+    /// search for a mate in x moves
+    pub mate: Option<usize>,
+    /// exact time to search
+    pub movetime: Option<u64>,
+    /// search until the stop command
+    pub infinite: bool,
+    /// called with the progress of the search, the search is silent if None
+    pub reporter: Option<SearchReporter>,
+}
+
+/// Flags shared between a running search and the thread controlling it
+#[derive(Debug, Default)]
+pub struct SearchSignals {
+    /// raised to stop the search as soon as possible
+    pub stop: AtomicBool,
+    /// raised while pondering, lowering it (ponderhit) turns the search into a normal one
+    pub ponder: AtomicBool,
+}
+
+/// Time allowed for a move
+/// no new iteration is started after the soft limit, the search is aborted at the hard limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeBudget {
+    pub soft: Duration,
+    pub hard: Duration,
+}
+
+impl TimeBudget {
+    /// exactly movetime for the move, minus the move overhead
+    fn fixed(movetime: u64, move_overhead: u64) -> Self {
+        let time = Duration::from_millis(movetime.saturating_sub(move_overhead).max(1));
+        Self {
+            soft: time,
+            hard: time,
+        }
+    }
+}
+
+impl SearchLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// limits for a fixed depth search
+    pub fn depth(depth: usize) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    /// true if none of the limits has been set, the search would never end
+    pub fn is_unbounded(&self) -> bool {
+        !self.infinite
+            && self.depth.is_none()
+            && self.nodes.is_none()
+            && self.mate.is_none()
+            && self.movetime.is_none()
+            && self.wtime.is_none()
+            && self.btime.is_none()
+    }
+
+    /// maximum depth to search in plies
+    pub fn max_depth(&self) -> Option<usize> {
+        match (self.depth, self.mate) {
+            (Some(depth), _) => Some(depth),
+            // a mate in x moves is found in 2x - 1 plies
+            (None, Some(mate)) => Some((2 * mate).saturating_sub(1)),
+            (None, None) => None,
+        }
+    }
+
+    /// computes the time budget for a move of side
+    /// move_overhead is kept in reserve to account for the communication with the GUI
+    /// None means there's no time limit
+    pub fn time_budget(&self, side: Color, move_overhead: u64) -> Option<TimeBudget> {
+        if self.infinite {
+            return None;
+        }
+
+        if let Some(movetime) = self.movetime {
+            return Some(TimeBudget::fixed(movetime, move_overhead));
+        }
+
+        let (time, inc) = match side {
+            Color::White => (self.wtime, self.winc.unwrap_or(0)),
+            Color::Black => (self.btime, self.binc.unwrap_or(0)),
+        };
+        let time = match time {
+            Some(time) => time,
+            // the search is still timed, it would never end otherwise
+            None if self.wtime.is_some() || self.btime.is_some() => {
+                return Some(TimeBudget::fixed(MISSING_CLOCK_MOVE_TIME, move_overhead))
+            }
+            None => return None,
+        };
+        let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        // never use more than what's left on the clock, and keep some
+        // time for the next moves unless this is the last one before the time control
+        let mut max_time = time.saturating_sub(move_overhead);
+        if moves_to_go > 1 {
+            max_time = max_time.min(time / 2);
+        }
+        let max_time = max_time.max(1);
+
+        let soft = time / moves_to_go + inc * 3 / 4;
+        let hard = (soft * HARD_LIMIT_FACTOR).min(max_time);
+        let soft = soft.min(hard);
+
+        Some(TimeBudget {
+            soft: Duration::from_millis(soft),
+            hard: Duration::from_millis(hard),
+        })
+    }
+}
@@ -0,0 +1,164 @@
+use chessire_utils::board::Coord;
+use chessire_utils::color::Color;
+use chessire_utils::moves::Move;
+use chessire_utils::piece::Piece;
+
+// no chess position has more than 218 legal moves
+pub const MAX_MOVES: usize = 256;
+
+/// A move along with the score used to order it
+#[derive(Debug, Clone, Copy)]
+pub struct ScoredMove {
+    pub mov: Move,
+    pub score: i32,
+}
+
+/// Fixed capacity list of moves that lives on the stack,
+/// move generation pushes into it without allocating
+/// only the first len slots hold moves, the rest are never read
+#[derive(Debug, Clone, Copy)]
+pub struct MoveList {
+    moves: [ScoredMove; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        let empty = ScoredMove {
+            mov: Move::new(
+                Coord::from_tile(0),
+                Coord::from_tile(0),
+                Piece::Pawn(Color::White),
+                None,
+            ),
+            score: 0,
+        };
+        Self {
+            moves: [empty; MAX_MOVES],
+            len: 0,
+        }
+    }
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn push(&mut self, mov: Move) {
+        debug_assert!(self.len < MAX_MOVES, "move list overflow");
+        self.moves[self.len] = ScoredMove { mov, score: 0 };
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// move at index, panics if out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Move {
+        self.moves[..self.len][index].mov
+    }
+
+    /// score of the move at index, panics if out of bounds
+    #[inline]
+    pub fn score(&self, index: usize) -> i32 {
+        self.moves[..self.len][index].score
+    }
+
+    #[inline]
+    pub fn set_score(&mut self, index: usize, score: i32) {
+        self.moves[..self.len][index].score = score;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Move> + '_ {
+        self.moves[..self.len].iter().map(|m| m.mov)
+    }
+
+    /// keeps only the moves for which f returns true, in the same order
+    pub fn retain(&mut self, mut f: impl FnMut(Move) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(self.get(i)) {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
+    /// scores every move with f
+    pub fn score_moves(&mut self, mut f: impl FnMut(Move) -> i32) {
+        for entry in self.moves[..self.len].iter_mut() {
+            entry.score = f(entry.mov);
+        }
+    }
+
+    /// sorts the moves from the highest to the lowest score
+    pub fn sort(&mut self) {
+        self.moves[..self.len].sort_unstable_by_key(|m| -m.score);
+    }
+
+    /// moves the highest scored move among the ones from start to the end into start, and
+    /// returns it. Picking the moves one by one is cheaper than sorting when the search is
+    /// likely to stop after the first few
+    pub fn pick_best(&mut self, start: usize) -> Option<Move> {
+        if start >= self.len {
+            return None;
+        }
+        let mut best = start;
+        for i in start + 1..self.len {
+            if self.score(i) > self.score(best) {
+                best = i;
+            }
+        }
+        self.moves.swap(start, best);
+        Some(self.get(start))
+    }
+
+    pub fn to_vec(&self) -> Vec<Move> {
+        self.iter().collect()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = MoveListIntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MoveListIntoIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+pub struct MoveListIntoIter {
+    list: MoveList,
+    index: usize,
+}
+
+impl Iterator for MoveListIntoIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.list.len {
+            self.index += 1;
+            Some(self.list.get(self.index - 1))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+/// Type of an engine option, along with its default value and constraints
+/// see the option command in the UCI protocol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionType {
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Check {
+        default: bool,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    Button,
+    String {
+        default: &'static str,
+    },
+}
+
+/// Value given to an option, already validated against its type
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Spin(i64),
+    Check(bool),
+    Combo(String),
+    Button,
+    String(String),
+}
+
+/// An option that can be changed by the user with setoption
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngineOption {
+    pub name: &'static str,
+    pub kind: OptionType,
+}
+
+impl EngineOption {
+    pub const fn new(name: &'static str, kind: OptionType) -> Self {
+        Self { name, kind }
+    }
+
+    /// value the option takes when the engine starts
+    pub fn default_value(&self) -> OptionValue {
+        match self.kind {
+            OptionType::Spin { default, .. } => OptionValue::Spin(default),
+            OptionType::Check { default } => OptionValue::Check(default),
+            OptionType::Combo { default, .. } => OptionValue::Combo(default.to_string()),
+            OptionType::Button => OptionValue::Button,
+            OptionType::String { default } => OptionValue::String(default.to_string()),
+        }
+    }
+
+    /// parses and validates a value for this option
+    pub fn parse(&self, value: Option<&str>) -> Result<OptionValue, String> {
+        match (self.kind, value) {
+            (OptionType::Button, _) => Ok(OptionValue::Button),
+            (_, None) => Err(format!("option {} requires a value", self.name)),
+            (OptionType::Spin { min, max, .. }, Some(value)) => match value.parse::<i64>() {
+                Ok(x) if (min..=max).contains(&x) => Ok(OptionValue::Spin(x)),
+                Ok(x) => Err(format!(
+                    "value {} for option {} out of range [{}, {}]",
+                    x, self.name, min, max
+                )),
+                Err(_) => Err(format!("invalid value {} for option {}", value, self.name)),
+            },
+            (OptionType::Check { .. }, Some(value)) => match value.to_lowercase().as_str() {
+                "true" => Ok(OptionValue::Check(true)),
+                "false" => Ok(OptionValue::Check(false)),
+                _ => Err(format!("invalid value {} for option {}", value, self.name)),
+            },
+            (OptionType::Combo { vars, .. }, Some(value)) => vars
+                .iter()
+                .find(|var| var.eq_ignore_ascii_case(value))
+                .map(|var| OptionValue::Combo(var.to_string()))
+                .ok_or_else(|| format!("invalid value {} for option {}", value, self.name)),
+            (OptionType::String { .. }, Some(value)) => Ok(OptionValue::String(value.to_string())),
+        }
+    }
+}
+
+/// prints the option as expected by the uci command
+impl fmt::Display for EngineOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match self.kind {
+            OptionType::Spin { default, min, max } => {
+                write!(f, "spin default {} min {} max {}", default, min, max)
+            }
+            OptionType::Check { default } => write!(f, "check default {}", default),
+            OptionType::Combo { default, vars } => {
+                write!(f, "combo default {}", default)?;
+                for var in vars {
+                    write!(f, " var {}", var)?;
+                }
+                Ok(())
+            }
+            OptionType::Button => write!(f, "button"),
+            OptionType::String { default } => {
+                // empty strings are sent as <empty>
+                let default = if default.is_empty() {
+                    "<empty>"
+                } else {
+                    default
+                };
+                write!(f, "string default {}", default)
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use super::Score;
+use chessire_utils::moves::Move;
+
+/// Function called by the search to report its progress
+pub type SearchReporter = fn(SearchReport);
+
+/// Score of a position as reported to the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportedScore {
+    /// score in centipawns from the point of view of the side to move
+    Centipawns(Score),
+    /// mate in x moves, negative if the side to move is getting mated
+    Mate(i32),
+}
+
+/// Statistics of a completed iteration
+#[derive(Debug, Clone)]
+pub struct IterationReport {
+    pub depth: usize,
+    /// index of the line, starting from 1, when searching several principal variations
+    pub multipv: usize,
+    /// deepest ply reached
+    pub seldepth: usize,
+    pub score: ReportedScore,
+    /// nodes visited, including the quiescence nodes
+    pub nodes: u64,
+    /// nodes visited by the quiescence search
+    pub qnodes: u64,
+    /// permill of the transposition table in use
+    pub hashfull: usize,
+    /// milliseconds since the start of the search
+    pub time: u64,
+    pub pv: Vec<Move>,
+}
+
+/// Progress of a running search
+#[derive(Debug, Clone)]
+pub enum SearchReport {
+    /// an iteration of the iterative deepening has been completed
+    Iteration(IterationReport),
+    /// the root move being searched, numbered from 1
+    CurrentMove {
+        depth: usize,
+        mov: Move,
+        number: usize,
+    },
+}
@@ -1,45 +1,82 @@
 use super::*;
+use chessire_utils::moves::Move;
+use engine::limits::SearchLimits;
+use engine::report::*;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
-pub fn uci_loop(engine: &mut impl ChessEngine) {
+// fixed depth used by the go command
+const UCI_SEARCH_DEPTH: usize = 4;
+
+// set by the debug command, the search sends extra information as text
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+pub fn uci_loop<E>(engine: &mut E)
+where
+    E: ChessEngine + Clone + Send + 'static,
+{
     let mut stdin = String::new();
+    // the search runs on its own thread, which gives the engine back when it's done
+    let mut search: Option<JoinHandle<E>> = None;
 
-    while let Ok(_n) = io::stdin().read_line(&mut stdin) {
-        if let Some(cmd_str) = stdin.trim().to_lowercase().lines().last() {
+    while let Ok(n) = io::stdin().read_line(&mut stdin) {
+        // end of input, the GUI is gone
+        if n == 0 {
+            break;
+        }
+        // only the command is lowercased, FEN strings are case sensitive!
+        if let Some(cmd_str) = stdin.trim().lines().last() {
             let mut cmd = cmd_str.split_ascii_whitespace();
-            match cmd.next().unwrap_or("") {
+            let command = cmd.next().unwrap_or("").to_lowercase();
+
+            // only a few commands can be answered while searching, any other one ends the search
+            if !matches!(command.as_str(), "isready" | "ponderhit" | "debug") {
+                uci_stop(engine, &mut search);
+            }
+
+            match command.as_str() {
                 // debug command from stockfish
                 "d" => stockfish_style_debug(engine),
+                // evaluation breakdown, also from stockfish
+                "eval" => uci_eval(engine),
                 // written by the order they appear in wbec-ridderker.nl/html/UCIProtocol.html
-                "uci" => uci_print_info(),
+                "uci" => uci_print_info(engine),
                 "debug" => uci_debug(cmd),
                 "isready" => uci_is_ready(),
-                "setoption" => uci_set_option(cmd),
+                "setoption" => uci_set_option(cmd, engine),
                 "register" => uci_register(cmd),
                 "ucinewgame" => uci_new_game(cmd, engine),
                 "position" => uci_position(cmd, engine),
-                "go" => uci_go(cmd, engine),
-                "stop" => uci_stop(cmd),
-                "ponderhit" => uci_ponderhit(cmd),
+                "go" => uci_go(cmd, engine, &mut search),
+                // the search has been already stopped
+                "stop" => (),
+                "ponderhit" => uci_ponderhit(engine),
                 "quit" => break,
-                _ => uci_unkown_command(cmd),
+                _ => uci_unkown_command(cmd_str),
             }
         }
+        stdin.clear();
     }
+    uci_stop(engine, &mut search);
 }
 
 fn stockfish_style_debug(engine: &impl ChessEngine) {
     println!("{}", engine.get_internal_position());
 }
 
-fn uci_print_info() {
+fn uci_eval(engine: &impl ChessEngine) {
+    println!("{}", engine.get_evaluation_details());
+}
+
+fn uci_print_info(engine: &impl ChessEngine) {
     //TODO: fix this
     println!("id name {}", "chessire");
     println!("id author Xavi Ondono");
-    println!(
-        "option name Hash type spin min 1 max {} default 128",
-        1024 * 1024
-    );
+    for option in engine.options() {
+        println!("{}", option);
+    }
     println!("uciok");
 }
 
@@ -49,77 +86,287 @@ fn uci_is_ready() {
 }
 
 fn uci_debug(mut cmd: std::str::SplitAsciiWhitespace) {
-    if cmd.next().unwrap_or("") == "on" {
-        // enable debug mode
-        println!("info debug enabled");
+    match cmd.next().unwrap_or("") {
+        "on" => {
+            DEBUG.store(true, Ordering::Relaxed);
+            println!("info debug enabled");
+        }
+        "off" => DEBUG.store(false, Ordering::Relaxed),
+        _ => (),
     }
 }
 
-fn uci_unkown_command(mut cmd: std::str::SplitAsciiWhitespace) {
-    println!("[ERROR] unkown UCI command {}", cmd.next().unwrap_or(""));
+fn uci_unkown_command(cmd: &str) {
+    println!(
+        "[ERROR] unkown UCI command {}",
+        cmd.split_ascii_whitespace().next().unwrap_or("")
+    );
 }
 
-fn uci_set_option(_cmd: std::str::SplitAsciiWhitespace) {
-    // unimplemented!();
+fn uci_set_option(mut cmd: std::str::SplitAsciiWhitespace, engine: &mut impl ChessEngine) {
+    if cmd.next() != Some("name") {
+        println!("info string ERROR: setoption expects a name");
+        return;
+    }
+    // both the name and the value can contain spaces
+    let mut name = vec![];
+    for token in cmd.by_ref() {
+        if token == "value" {
+            break;
+        }
+        name.push(token);
+    }
+    let name = name.join(" ");
+    let value = cmd.collect::<Vec<&str>>().join(" ");
+    let value = if value.is_empty() {
+        None
+    } else {
+        Some(value.as_str())
+    };
+
+    // option names are case insensitive
+    match engine
+        .options()
+        .into_iter()
+        .find(|option| option.name.eq_ignore_ascii_case(&name))
+    {
+        Some(option) => match option
+            .parse(value)
+            .and_then(|value| engine.set_option(option.name, value))
+        {
+            Ok(()) => (),
+            Err(e) => println!("info string ERROR: {}", e),
+        },
+        None => println!("info string ERROR: unknown option {}", name),
+    }
 }
 
 fn uci_register(_cmd: std::str::SplitAsciiWhitespace) {
     //unimplemented!()
 }
 
-fn uci_new_game(_cmd: std::str::SplitAsciiWhitespace, _engine: &mut impl ChessEngine) {
-    //engine.set_position();
+fn uci_new_game(_cmd: std::str::SplitAsciiWhitespace, engine: &mut impl ChessEngine) {
+    engine.new_game();
 }
 
-fn uci_position(mut cmd: std::str::SplitAsciiWhitespace, _engine: &mut impl ChessEngine) {
+/// sets up the position of the position command
+/// moves are played until the first one that can't be parsed or is illegal, which is reported
+pub fn uci_position(mut cmd: std::str::SplitAsciiWhitespace, engine: &mut impl ChessEngine) {
     match cmd.next().unwrap_or("") {
         "startpos" => {
-            //board.reset_position();
+            engine.set_start_position();
             // now we need to handle the moves list
-            match cmd.next().unwrap_or("") {
-                "moves" => {
-                    for _m in cmd {
-                        //let mov = Move::from_algebraic(board.clone(), m);
-                        //board.apply_move(mov);
-                    }
-                }
-                _ => println!("ups!"),
+            match cmd.next() {
+                Some("moves") => uci_apply_moves(cmd, engine),
+                None => (),
+                Some(x) => println!("info string ERROR: unexpected token {} after startpos", x),
             }
         }
         "fen" => {
-            //board.set_position_from_fen(cmd.next().unwrap_or(""));
+            // the FEN string runs until the moves list (if any)
+            let mut fields = vec![];
+            for field in cmd.by_ref() {
+                if field == "moves" {
+                    break;
+                }
+                fields.push(field);
+            }
+            // some GUIs skip the clocks, use the defaults
+            if fields.len() == 4 {
+                fields.push("0");
+                fields.push("1");
+            }
+            if fields.len() != 6 {
+                println!(
+                    "info string ERROR: FEN should have 6 fields, found {}",
+                    fields.len()
+                );
+                return;
+            }
+
+            let fen = fields.join(" ");
+            let mut g = ChessGame::new();
+            g.clear();
+            if g.apply_fen(&fen).is_err() {
+                println!("info string ERROR: unable to parse FEN {}", fen);
+                return;
+            }
+            engine.set_position(g);
+            uci_apply_moves(cmd, engine);
         }
         _ => println!("info string ERROR: uci_position function failure!"),
     }
 }
 
-fn uci_go(mut cmd: std::str::SplitAsciiWhitespace, engine: &mut impl ChessEngine) {
-    // ignore parameters for now
-    match cmd.next().unwrap_or("") {
-        "searchmoves" => (),
-        "ponder" => (),
-        "wtime" => (),
-        "btime" => (),
-        "winc" => (),
-        "binc" => (),
-        "movestogo" => (),
-        "depth" => (),
-        "nodes" => (),
-        "mate" => (),
-        "movetime" => (),
-        "infinite" => (),
-        _ => (),
-    };
+/// plays a list of moves in long algebraic notation (e2e4, e7e8q...) on the engine
+/// stops at the first move that can't be parsed or is illegal, the moves before it are kept
+fn uci_apply_moves(cmd: std::str::SplitAsciiWhitespace, engine: &mut impl ChessEngine) {
+    for m in cmd {
+        // moves not found in the move list are either malformed or illegal
+        let legal = match uci_parse_move(engine, m) {
+            Some(mov) => engine.make_move(mov).is_ok(),
+            None => false,
+        };
+        if !legal {
+            println!("info string ERROR: illegal or malformed move {}", m);
+            return;
+        }
+    }
+}
+
+/// finds the move matching the long algebraic notation string in the engine's move list
+fn uci_parse_move(engine: &impl ChessEngine, m: &str) -> Option<Move> {
+    let side = engine.get_internal_position().side_to_move;
+    engine
+        .get_moves(side)
+        .into_iter()
+        .find(|mov| move_to_uci(*mov) == m.to_lowercase())
+}
+
+fn uci_go<E>(
+    cmd: std::str::SplitAsciiWhitespace,
+    engine: &mut E,
+    search: &mut Option<JoinHandle<E>>,
+) where
+    E: ChessEngine + Clone + Send + 'static,
+{
+    let mut limits = uci_parse_go(cmd, engine);
+    limits.reporter = Some(uci_print_search_info);
+
+    // a plain go command, don't search forever
+    if limits.is_unbounded() {
+        limits.depth = Some(UCI_SEARCH_DEPTH);
+    }
+
+    let signals = engine.signals();
+    signals.stop.store(false, Ordering::SeqCst);
+    signals.ponder.store(limits.ponder, Ordering::SeqCst);
+
+    let mut searcher = engine.clone();
+    *search = Some(thread::spawn(move || {
+        searcher.search_best_move(&limits);
+
+        // when pondering or in infinite mode the best move can't be sent before stop or ponderhit
+        while (limits.infinite || signals.ponder.load(Ordering::SeqCst))
+            && !signals.stop.load(Ordering::SeqCst)
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+        uci_print_best_move(&searcher);
+        searcher
+    }));
+}
+
+/// prints the progress of the search as info lines
+fn uci_print_search_info(report: SearchReport) {
+    match report {
+        SearchReport::Iteration(it) => {
+            let score = match it.score {
+                ReportedScore::Centipawns(cp) => format!("cp {}", cp),
+                ReportedScore::Mate(moves) => format!("mate {}", moves),
+            };
+            let pv: Vec<String> = it.pv.iter().map(|m| move_to_uci(*m)).collect();
+            println!(
+                "info depth {} seldepth {} multipv {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+                it.depth,
+                it.seldepth,
+                it.multipv,
+                score,
+                it.nodes,
+                it.nodes * 1000 / it.time.max(1),
+                it.hashfull,
+                it.time,
+                pv.join(" ")
+            );
+            // uci has no field for them, the GUI shows it as text
+            if DEBUG.load(Ordering::Relaxed) {
+                println!("info string qnodes {}", it.qnodes);
+            }
+        }
+        SearchReport::CurrentMove { depth, mov, number } => println!(
+            "info depth {} currmove {} currmovenumber {}",
+            depth,
+            move_to_uci(mov),
+            number
+        ),
+    }
+}
+
+/// prints the result of the last search, along with the expected reply to ponder on
+fn uci_print_best_move(engine: &impl ChessEngine) {
+    let pv = engine.get_principal_variation();
+    match pv.as_slice() {
+        [] => println!("bestmove 0000"),
+        [mov] => println!("bestmove {}", move_to_uci(*mov)),
+        [mov, reply, ..] => println!(
+            "bestmove {} ponder {}",
+            move_to_uci(*mov),
+            move_to_uci(*reply)
+        ),
+    }
+}
+
+/// builds the search limits from the parameters of the go command
+pub fn uci_parse_go(
+    cmd: std::str::SplitAsciiWhitespace,
+    engine: &impl ChessEngine,
+) -> SearchLimits {
+    let mut limits = SearchLimits::new();
+    let mut cmd = cmd.peekable();
+
+    while let Some(param) = cmd.next() {
+        match param {
+            "searchmoves" => {
+                // the list of moves runs until the next parameter
+                while let Some(mov) = cmd.peek().and_then(|m| uci_parse_move(engine, m)) {
+                    limits.searchmoves.push(mov);
+                    cmd.next();
+                }
+            }
+            "ponder" => limits.ponder = true,
+            "infinite" => limits.infinite = true,
+            "wtime" => limits.wtime = uci_parse_value(cmd.next()),
+            "btime" => limits.btime = uci_parse_value(cmd.next()),
+            "winc" => limits.winc = uci_parse_value(cmd.next()),
+            "binc" => limits.binc = uci_parse_value(cmd.next()),
+            "movestogo" => limits.movestogo = uci_parse_value(cmd.next()),
+            "depth" => limits.depth = uci_parse_value(cmd.next()),
+            "nodes" => limits.nodes = uci_parse_value(cmd.next()),
+            "mate" => limits.mate = uci_parse_value(cmd.next()),
+            "movetime" => limits.movetime = uci_parse_value(cmd.next()),
+            x => println!("info string ERROR: unknown go parameter {}", x),
+        }
+    }
+    limits
+}
 
-    // print the best move
-    let mov = engine.get_best_move();
-    println!("bestmove {}", mov);
+/// parses the value of a numeric parameter
+/// some GUIs send negative times when the clock runs out, those are clamped to 0
+fn uci_parse_value<T: std::str::FromStr + Default>(value: Option<&str>) -> Option<T> {
+    let value = value?;
+    match value.parse::<T>() {
+        Ok(x) => Some(x),
+        Err(_) if value.starts_with('-') => Some(T::default()),
+        Err(_) => {
+            println!("info string ERROR: invalid value {}", value);
+            None
+        }
+    }
 }
 
-fn uci_stop(_cmd: std::str::SplitAsciiWhitespace) {
-    unimplemented!();
+/// stops the running search, if any, and waits for it to send its best move
+fn uci_stop<E: ChessEngine>(engine: &mut E, search: &mut Option<JoinHandle<E>>) {
+    if let Some(handle) = search.take() {
+        engine.signals().stop.store(true, Ordering::SeqCst);
+        // get the engine back with the search results
+        match handle.join() {
+            Ok(searcher) => *engine = searcher,
+            Err(_) => println!("info string ERROR: search thread panicked"),
+        }
+    }
 }
 
-fn uci_ponderhit(_cmd: std::str::SplitAsciiWhitespace) {
-    unimplemented!();
+/// the opponent played the expected move, keep searching as a normal search
+fn uci_ponderhit(engine: &impl ChessEngine) {
+    engine.signals().ponder.store(false, Ordering::SeqCst);
 }
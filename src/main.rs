@@ -27,6 +27,9 @@ struct Args {
     run: Run,
     #[clap(short, long, arg_enum, default_value_t = Implementation::Bitboard)]
     implementation: Implementation,
+    /// FEN of the position the cli game starts from
+    #[clap(short, long)]
+    fen: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -48,7 +51,14 @@ fn main() {
         use Run::*;
         match args.run {
             Cli => {
-                cli_loop(&mut engine);
+                let mut g = chessire::ChessGame::new();
+                if let Some(fen) = &args.fen {
+                    if g.apply_fen(fen).is_err() {
+                        println!("Unable to parse FEN {}", fen);
+                        return;
+                    }
+                }
+                cli_loop(&mut engine, g);
             }
             Uci => {
                 uci_loop(&mut engine);
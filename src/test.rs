@@ -138,6 +138,31 @@ pub const POSITION3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1 ";
 pub const POSITION4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
 pub const POSITION5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8  ";
 
+/// engine set up in the position of a FEN string
+pub fn engine_from_fen(fen: &str) -> BitBoardEngine {
+    let mut game = ChessGame::new();
+    game.apply_fen(fen.trim())
+        .unwrap_or_else(|_| panic!("error while parsing FEN string {}", fen));
+    BitBoardEngine::new_engine(game)
+}
+
+/// legal move of the engine's position in long algebraic notation, panics if there's none
+pub fn find_move(engine: &BitBoardEngine, uci: &str) -> chessire_utils::moves::Move {
+    engine
+        .get_legal_moves()
+        .into_iter()
+        .find(|mov| move_to_uci(*mov) == uci)
+        .unwrap_or_else(|| panic!("move {} not found in {}", uci, engine.state.get_game()))
+}
+
+/// plays the moves in long algebraic notation on the engine
+pub fn play(engine: &mut BitBoardEngine, moves: &[&str]) {
+    for uci in moves {
+        let mov = find_move(engine, uci);
+        engine.make_move(mov).unwrap();
+    }
+}
+
 /** Depth Nodes Captures Enpassant Castles Promotions Checks DiscoveryChecks DoubleChecks Checkmates **/
 static POS1_PERFT_RESULTS: &[PerfResults] = &[
     PerfResults::new(1, 20, 0, 0, 0, 0, 0, 0, 0, 0),
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test_cli {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// plays the moves in the cli game starting from fen, and returns what it printed
+    /// q is sent at the end, so the game ends even if the moves don't end it
+    fn cli_output(fen: &str, moves: &[&str]) -> String {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_chessire"))
+            .args(["-r", "cli", "--fen", fen])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = process.stdin.take().unwrap();
+        for mov in moves {
+            writeln!(stdin, "{}", mov).unwrap();
+        }
+        // the game may be over already, and the pipe closed
+        writeln!(stdin, "q").unwrap_or(());
+        drop(stdin);
+        let output = process.wait_with_output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn draws_are_announced() {
+        // the knight takes the last pawn
+        let output = cli_output("4k3/8/8/8/8/2p5/8/1N2K3 w - - 0 1", &["b1c3"]);
+        assert!(
+            output
+                .trim_end()
+                .ends_with("Draw by insufficient material!"),
+            "{}",
+            output
+        );
+
+        // the hundredth half move without a capture or a pawn move
+        let output = cli_output("4k3/8/8/8/8/8/R7/4K3 w - - 99 80", &["a2a3"]);
+        assert!(
+            output.trim_end().ends_with("Draw by the fifty move rule!"),
+            "{}",
+            output
+        );
+
+        // a game can't start in a drawn position either
+        let output = cli_output("4k3/8/8/8/8/8/8/4K3 w - - 0 1", &[]);
+        assert!(
+            output
+                .trim_end()
+                .ends_with("Draw by insufficient material!"),
+            "{}",
+            output
+        );
+
+        // otherwise the engine replies and the game goes on
+        let output = cli_output("4k3/8/8/8/8/8/R7/4K3 w - - 0 1", &["a2a3"]);
+        assert!(!output.contains("Draw"), "{}", output);
+    }
+}
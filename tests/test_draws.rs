@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod test_draws {
+    use chessire::outcome::GameOutcome;
+    use chessire::test::{engine_from_fen, play};
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+
+    #[test]
+    fn threefold_repetition() {
+        let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        play(&mut engine, &shuffle);
+        // the start position has been reached twice
+        assert_eq!(engine.game_outcome(), None);
+        // but inside a search once is enough
+        assert!(engine.is_repetition(4));
+        play(&mut engine, &shuffle);
+        assert_eq!(engine.game_outcome(), Some(GameOutcome::Repetition));
+    }
+
+    #[test]
+    fn fifty_moves() {
+        let mut engine = engine_from_fen("8/8/3k4/8/8/3K4/8/R7 w - - 99 80");
+        assert_eq!(engine.game_outcome(), None);
+        play(&mut engine, &["a1a2"]);
+        assert_eq!(engine.game_outcome(), Some(GameOutcome::FiftyMoves));
+        // a pawn move resets the counter
+        let mut engine = engine_from_fen("8/8/3k4/8/8/3K4/P7/8 w - - 99 80");
+        play(&mut engine, &["a2a3"]);
+        assert_eq!(engine.game_outcome(), None);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        for fen in [
+            "8/8/3k4/8/8/3K4/8/8 w - - 0 1",
+            "8/8/3k4/8/8/3K4/8/5B2 w - - 0 1",
+            "8/8/3k4/8/8/3K4/8/6n1 w - - 0 1",
+            // both bishops on dark squares
+            "8/8/3k4/8/8/3K4/8/2B3b1 w - - 0 1",
+        ] {
+            let engine = engine_from_fen(fen);
+            assert_eq!(
+                engine.game_outcome(),
+                Some(GameOutcome::InsufficientMaterial),
+                "{}",
+                fen
+            );
+        }
+        for fen in [
+            // bishops on squares of different colours
+            "8/8/3k4/8/8/3K4/8/2B2b2 w - - 0 1",
+            "8/8/3k4/8/8/3K4/8/1NN5 w - - 0 1",
+            "8/8/3k4/8/8/3K4/7p/8 w - - 0 1",
+        ] {
+            let engine = engine_from_fen(fen);
+            assert_eq!(engine.game_outcome(), None, "{}", fen);
+        }
+    }
+}
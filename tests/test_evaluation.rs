@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod test_evaluation {
+    use chessire::evaluation::TaperedScore;
+    use chessire::test::{engine_from_fen, play};
+    use chessire::trace::EvalTerm;
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+    use chessire_utils::color::Color::{Black, White};
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        engine_from_fen(fen).evaluate()
+    }
+
+    #[test]
+    fn start_position_is_balanced() {
+        assert_eq!(
+            evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            0
+        );
+    }
+
+    #[test]
+    fn mirrored_positions_have_opposite_scores() {
+        let white =
+            evaluate_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let black =
+            evaluate_fen("r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1");
+        assert_ne!(white, 0);
+        assert_eq!(white, -black);
+    }
+
+    #[test]
+    fn extra_material_wins() {
+        // a queen up in the endgame
+        assert!(evaluate_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1") > 800);
+        assert!(evaluate_fen("3qk3/8/8/8/8/8/8/4K3 w - - 0 1") < -800);
+    }
+
+    #[test]
+    fn passed_pawns_are_worth_more_closer_to_promotion() {
+        let far = engine_from_fen("4k3/8/8/8/8/3P4/8/4K3 w - - 0 1").pawn_structure();
+        let close = engine_from_fen("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1").pawn_structure();
+        // both are isolated, like the blocked pawns
+        let blocked = engine_from_fen("4k3/3p4/8/8/8/3P4/8/4K3 w - - 0 1").pawn_structure();
+        assert!(far.mg > blocked.mg && far.eg > blocked.eg);
+        assert!(close.mg > far.mg && close.eg > far.eg);
+        // more so in the endgame
+        assert!(close.eg > close.mg);
+    }
+
+    #[test]
+    fn weak_pawns_are_penalised() {
+        // doubled and isolated pawns on the c file, blocked by an isolated pawn
+        let weak = engine_from_fen("4k3/2p5/8/8/8/2P5/2P5/4K3 w - - 0 1").pawn_structure();
+        assert!(weak.mg < 0 && weak.eg < 0);
+        // the same pawns, connected
+        let connected = engine_from_fen("4k3/2p5/8/8/8/2P5/3P4/4K3 w - - 0 1").pawn_structure();
+        assert!(connected.mg > 0 && connected.eg > 0);
+    }
+
+    #[test]
+    fn pawn_key_only_changes_with_pawn_moves() {
+        let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+        let start = engine.state.pawn_hash;
+        play(&mut engine, &["g1f3"]);
+        assert_eq!(engine.state.pawn_hash, start);
+        play(&mut engine, &["e7e5"]);
+        assert_ne!(engine.state.pawn_hash, start);
+        assert_eq!(engine.state.pawn_hash, engine.state.compute_pawn_hash());
+    }
+
+    #[test]
+    fn rooks_prefer_open_files() {
+        let open = engine_from_fen("4k3/pp3ppp/8/8/8/8/PP3PPP/3RK3 w - - 0 1").piece_activity();
+        let closed = engine_from_fen("4k3/pp3ppp/8/8/8/8/PP3PPP/5RK1 w - - 0 1").piece_activity();
+        assert!(open.mg > closed.mg && open.eg > closed.eg);
+    }
+
+    #[test]
+    fn kings_prefer_a_pawn_shield() {
+        let sheltered = engine_from_fen("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1").king_safety();
+        assert_eq!(sheltered.mg, 0);
+        // a pawn is missing from white's shield, leaving the g file half open
+        let exposed = engine_from_fen("6k1/5ppp/8/8/8/8/5P1P/6K1 w - - 0 1").king_safety();
+        assert!(exposed.mg < 0);
+        assert_eq!(exposed.eg, 0);
+    }
+
+    #[test]
+    fn incremental_eval_matches_recompute() {
+        // castling and en passant in kiwipete, promotions with and without captures
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        for fen in fens.iter() {
+            let mut engine = engine_from_fen(fen);
+            let (psqt, phase) = (engine.state.psqt, engine.state.phase);
+            for mov in engine.get_legal_moves() {
+                engine.make_move(mov).unwrap();
+                assert_eq!(
+                    engine.state.psqt,
+                    engine.state.compute_psqt(&engine.eval_params, None)
+                );
+                assert_eq!(engine.state.phase, engine.state.compute_phase());
+                engine.unmake_move();
+                assert_eq!((engine.state.psqt, engine.state.phase), (psqt, phase));
+            }
+        }
+    }
+
+    #[test]
+    fn trace_adds_up_to_the_evaluation() {
+        let engine =
+            engine_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let trace = engine.trace_eval();
+        assert_eq!(trace.score(), engine.evaluate());
+        let table = trace.to_string();
+        assert!(table.contains("Mobility"));
+        assert!(table.contains(&format!("Evaluation: {}", engine.evaluate())));
+    }
+
+    #[test]
+    fn trace_splits_the_pawn_and_king_terms() {
+        // doubled and isolated a pawns, a passed h pawn, and a black king without a shield
+        let engine = engine_from_fen("4k3/8/8/8/8/P7/P4PPP/6K1 w - - 0 1");
+        let trace = engine.trace_eval();
+        let white = |term: EvalTerm| trace.terms[term as usize][White as usize];
+        let black = |term: EvalTerm| trace.terms[term as usize][Black as usize];
+        let sum = |terms: &[EvalTerm]| {
+            terms.iter().fold(TaperedScore::default(), |total, term| {
+                total + trace.term_total(*term)
+            })
+        };
+
+        let pawn_terms = [
+            EvalTerm::DoubledPawns,
+            EvalTerm::IsolatedPawns,
+            EvalTerm::BackwardPawns,
+            EvalTerm::ConnectedPawns,
+            EvalTerm::PassedPawns,
+        ];
+        assert_eq!(sum(&pawn_terms), engine.pawn_structure());
+        assert!(white(EvalTerm::DoubledPawns).eg < 0);
+        assert!(white(EvalTerm::IsolatedPawns).eg < 0);
+        assert!(white(EvalTerm::ConnectedPawns).mg > 0);
+        assert!(white(EvalTerm::PassedPawns).eg > 0);
+        assert_eq!(black(EvalTerm::PassedPawns), TaperedScore::default());
+
+        let king_terms = [EvalTerm::PawnShield, EvalTerm::KingFiles];
+        assert_eq!(sum(&king_terms), engine.king_safety());
+        assert!(white(EvalTerm::PawnShield).mg > 0);
+        assert_eq!(black(EvalTerm::PawnShield), TaperedScore::default());
+        assert!(black(EvalTerm::KingFiles).mg < 0);
+
+        let table = trace.to_string();
+        assert!(table.contains("Connected pawns"));
+        assert!(table.contains("Pawn shield"));
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod test_hashing {
+    use chessire::test::{engine_from_fen, play};
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn incremental_hash_matches_recompute() {
+        // walk every legal move two plies deep, covering castling, en passant and promotions
+        let engine = engine_from_fen(KIWIPETE);
+        let side = engine.state.side_to_move;
+        for mov in engine.get_moves(side) {
+            let mut child = engine.clone();
+            if child.make_move(mov).is_err() {
+                continue;
+            }
+            assert_eq!(child.state.hash, child.state.compute_hash());
+            let side = child.state.side_to_move;
+            for mov in child.get_moves(side) {
+                let mut grandchild = child.clone();
+                if grandchild.make_move(mov).is_ok() {
+                    assert_eq!(grandchild.state.hash, grandchild.state.compute_hash());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transpositions_share_the_hash() {
+        let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+        let start = engine.state.hash;
+
+        play(&mut engine, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(engine.state.hash, start);
+
+        let mut a = engine.clone();
+        let mut b = engine.clone();
+        play(&mut a, &["e2e4", "e7e6", "d2d4"]);
+        play(&mut b, &["d2d4", "e7e6", "e2e4"]);
+        // the en passant square differs, so the keys must differ too
+        assert_ne!(a.state.hash, b.state.hash);
+
+        play(&mut a, &["d7d5"]);
+        play(&mut b, &["d7d5"]);
+        assert_eq!(a.state.hash, b.state.hash);
+    }
+
+    #[test]
+    fn side_to_move_changes_the_hash() {
+        let white = engine_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        let black = engine_from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_ne!(white.state.hash, black.state.hash);
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod test_limits {
+    use chessire::color::Color::{Black, White};
+    use chessire::engine::limits::{SearchLimits, TimeBudget};
+    use chessire::interface::uci_parse_go;
+    use chessire::move_to_uci;
+    use chessire::test::engine_from_fen;
+    use std::time::Duration;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn budget(soft: u64, hard: u64) -> Option<TimeBudget> {
+        Some(TimeBudget {
+            soft: Duration::from_millis(soft),
+            hard: Duration::from_millis(hard),
+        })
+    }
+
+    fn parse_go(params: &str) -> SearchLimits {
+        uci_parse_go(params.split_ascii_whitespace(), &engine_from_fen(START))
+    }
+
+    #[test]
+    fn fixed_time() {
+        let limits = SearchLimits {
+            movetime: Some(1000),
+            ..SearchLimits::new()
+        };
+        // the move overhead is kept in reserve
+        assert_eq!(limits.time_budget(White, 30), budget(970, 970));
+        assert_eq!(limits.time_budget(Black, 0), budget(1000, 1000));
+        // but the engine always gets some time
+        let limits = SearchLimits {
+            movetime: Some(10),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), budget(1, 1));
+    }
+
+    #[test]
+    fn clock_time() {
+        let limits = SearchLimits {
+            wtime: Some(60_000),
+            btime: Some(30_000),
+            winc: Some(1000),
+            ..SearchLimits::new()
+        };
+        // a share of the time left plus most of the increment, up to three times that
+        assert_eq!(limits.time_budget(White, 30), budget(2750, 8250));
+        assert_eq!(limits.time_budget(Black, 30), budget(1000, 3000));
+
+        // the clock of the side to move is the one that counts, without it the move
+        // gets a fixed time
+        let limits = SearchLimits {
+            wtime: Some(60_000),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(Black, 30), budget(970, 970));
+        assert_eq!(limits.time_budget(White, 30), budget(2000, 6000));
+
+        // no time limit at all
+        assert_eq!(SearchLimits::depth(5).time_budget(White, 30), None);
+        let limits = SearchLimits {
+            wtime: Some(60_000),
+            infinite: true,
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), None);
+    }
+
+    #[test]
+    fn time_control_and_low_time() {
+        // the last move before the time control can use everything but the overhead
+        let limits = SearchLimits {
+            wtime: Some(1000),
+            movestogo: Some(1),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), budget(970, 970));
+
+        // with moves left, no more than half of the clock is used
+        let limits = SearchLimits {
+            wtime: Some(10_000),
+            movestogo: Some(2),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), budget(5000, 5000));
+
+        let limits = SearchLimits {
+            wtime: Some(100),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), budget(3, 9));
+        let limits = SearchLimits {
+            wtime: Some(100),
+            winc: Some(1000),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.time_budget(White, 30), budget(50, 50));
+    }
+
+    #[test]
+    fn depth_limits() {
+        assert_eq!(SearchLimits::depth(7).max_depth(), Some(7));
+        assert_eq!(SearchLimits::new().max_depth(), None);
+        // a mate in x moves is found in 2x - 1 plies
+        let limits = SearchLimits {
+            mate: Some(3),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.max_depth(), Some(5));
+        let limits = SearchLimits {
+            mate: Some(3),
+            depth: Some(2),
+            ..SearchLimits::new()
+        };
+        assert_eq!(limits.max_depth(), Some(2));
+    }
+
+    #[test]
+    fn go_parameters() {
+        let limits = parse_go("wtime 60000 btime 50000 winc 1000 binc 500 movestogo 20");
+        assert_eq!(limits.wtime, Some(60_000));
+        assert_eq!(limits.btime, Some(50_000));
+        assert_eq!(limits.winc, Some(1000));
+        assert_eq!(limits.binc, Some(500));
+        assert_eq!(limits.movestogo, Some(20));
+        assert!(!limits.is_unbounded());
+
+        let limits = parse_go("depth 8 nodes 100000 mate 3 movetime 2000");
+        assert_eq!(limits.depth, Some(8));
+        assert_eq!(limits.nodes, Some(100_000));
+        assert_eq!(limits.mate, Some(3));
+        assert_eq!(limits.movetime, Some(2000));
+
+        let limits = parse_go("ponder infinite");
+        assert!(limits.ponder && limits.infinite);
+        assert!(parse_go("").is_unbounded());
+    }
+
+    #[test]
+    fn go_searchmoves_and_bad_values() {
+        // the list of moves ends at the next parameter
+        let limits = parse_go("searchmoves e2e4 g1f3 depth 5");
+        let moves: Vec<String> = limits.searchmoves.iter().map(|m| move_to_uci(*m)).collect();
+        assert_eq!(moves, ["e2e4", "g1f3"]);
+        assert_eq!(limits.depth, Some(5));
+
+        // a flag fell on the GUI's clock
+        let limits = parse_go("wtime -150 btime 1000");
+        assert_eq!(limits.wtime, Some(0));
+        assert_eq!(limits.btime, Some(1000));
+
+        let limits = parse_go("depth many nodes 10");
+        assert_eq!(limits.depth, None);
+        assert_eq!(limits.nodes, Some(10));
+    }
+}
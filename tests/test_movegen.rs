@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test_movegen {
+    use chessire::test::{engine_from_fen, POSITION1, POSITION2, POSITION3, POSITION4, POSITION5};
+    use chessire::{move_to_uci, BitBoardEngine, ChessEngine};
+
+    /// compares the legal generator with the moves tried one by one on the board, and with the
+    /// staged generators, for every position up to depth plies away
+    fn compare_generators(engine: &mut BitBoardEngine, depth: usize) {
+        let side = engine.state.side_to_move;
+        let mut legal: Vec<String> = engine
+            .get_legal_moves()
+            .into_iter()
+            .map(move_to_uci)
+            .collect();
+        let mut by_trial: Vec<String> = engine
+            .get_moves_by_trial(side)
+            .into_iter()
+            .map(move_to_uci)
+            .collect();
+        legal.sort();
+        by_trial.sort();
+        assert_eq!(
+            legal,
+            by_trial,
+            "generators differ in {}",
+            engine.state.get_game()
+        );
+
+        // the captures and the quiet moves split the legal moves in two
+        let mut staged: Vec<String> = engine
+            .get_captures()
+            .iter()
+            .chain(engine.get_quiets().iter())
+            .map(move_to_uci)
+            .collect();
+        staged.sort();
+        assert_eq!(
+            legal,
+            staged,
+            "staged generation differs in {}",
+            engine.state.get_game()
+        );
+
+        if engine.state.flags.in_check {
+            assert_eq!(engine.get_evasions().len(), legal.len());
+        } else {
+            assert!(engine.get_evasions().is_empty());
+        }
+
+        for mov in engine.get_quiet_checks() {
+            assert!(!mov.capture && mov.promoted_piece.is_none());
+            engine.make_move(mov).unwrap();
+            assert!(
+                engine.state.flags.in_check,
+                "{} doesn't give check",
+                move_to_uci(mov)
+            );
+            engine.unmake_move();
+        }
+
+        if depth > 1 {
+            for mov in engine.get_legal_moves() {
+                engine.make_move(mov).unwrap();
+                compare_generators(engine, depth - 1);
+                engine.unmake_move();
+            }
+        }
+    }
+
+    #[test]
+    fn legal_generator_matches_trial_generator() {
+        for fen in [POSITION1, POSITION2, POSITION3, POSITION4, POSITION5] {
+            let mut engine = engine_from_fen(fen);
+            compare_generators(&mut engine, 3);
+        }
+    }
+
+    #[test]
+    fn perft_node_counts() {
+        // known counts from the chessprogramming wiki perft results
+        let cases = [
+            (POSITION1, 4, 197_281),
+            (POSITION2, 4, 4_085_603),
+            (POSITION3, 5, 674_624),
+            (POSITION4, 4, 422_333),
+            (POSITION5, 4, 2_103_487),
+        ];
+        for (fen, depth, expected) in cases {
+            let mut engine = engine_from_fen(fen);
+            let mut nodes = 0;
+            engine.perft(depth, &mut nodes, false);
+            assert_eq!(nodes, expected, "depth {} of {}", depth, fen);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod test_movelist {
+    use chessire::engine::movelist::{MoveList, MAX_MOVES};
+    use chessire_utils::board::Coord;
+    use chessire_utils::color::Color::White;
+    use chessire_utils::moves::Move;
+    use chessire_utils::piece::Piece;
+
+    /// the n-th of MAX_MOVES different moves
+    fn nth_move(n: usize) -> Move {
+        Move::new(
+            Coord::from_tile(n / 64),
+            Coord::from_tile(n % 64),
+            Piece::Queen(White),
+            None,
+        )
+    }
+
+    /// inverse of nth_move
+    fn index_of(mov: Move) -> usize {
+        mov.source.to_usize() * 64 + mov.target.to_usize()
+    }
+
+    fn list_of(n: usize) -> MoveList {
+        let mut list = MoveList::new();
+        for i in 0..n {
+            list.push(nth_move(i));
+        }
+        list
+    }
+
+    fn indices(list: &MoveList) -> Vec<usize> {
+        list.iter().map(index_of).collect()
+    }
+
+    #[test]
+    fn push_up_to_capacity() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.iter().count(), 0);
+
+        let list_full = list_of(MAX_MOVES);
+        assert_eq!(list_full.len(), MAX_MOVES);
+        assert_eq!(indices(&list_full), (0..MAX_MOVES).collect::<Vec<_>>());
+        assert_eq!(index_of(list_full.get(MAX_MOVES - 1)), MAX_MOVES - 1);
+
+        list.push(nth_move(7));
+        assert_eq!(list.len(), 1);
+        list.clear();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn iteration() {
+        let list = list_of(10);
+        let by_value: Vec<usize> = list.into_iter().map(index_of).collect();
+        assert_eq!(by_value, (0..10).collect::<Vec<_>>());
+        assert_eq!(indices(&list), by_value);
+        assert_eq!(list.to_vec().len(), 10);
+        // iterating doesn't consume the list, it's copied
+        assert_eq!(list.len(), 10);
+    }
+
+    #[test]
+    fn retain_keeps_the_order() {
+        let mut list = list_of(20);
+        list.retain(|mov| index_of(mov).is_multiple_of(3));
+        assert_eq!(indices(&list), [0, 3, 6, 9, 12, 15, 18]);
+        list.retain(|_| false);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn scores_and_ordering() {
+        let mut list = list_of(6);
+        let scores = [10, -5, 40, 40, 0, 25];
+        list.score_moves(|mov| scores[index_of(mov)]);
+        for (i, score) in scores.iter().enumerate() {
+            assert_eq!(list.score(i), *score);
+        }
+
+        // picked from the highest to the lowest score, ties keep the first one
+        let mut picked = vec![];
+        while let Some(mov) = list.pick_best(picked.len()) {
+            picked.push(index_of(mov));
+        }
+        assert_eq!(picked, [2, 3, 5, 0, 4, 1]);
+        assert!(list.pick_best(6).is_none());
+
+        let mut list = list_of(6);
+        list.score_moves(|mov| scores[index_of(mov)]);
+        list.set_score(1, 100);
+        list.sort();
+        assert_eq!(index_of(list.get(0)), 1);
+        assert_eq!(list.score(0), 100);
+        assert_eq!(list.score(5), 0);
+    }
+}
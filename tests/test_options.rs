@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test_options {
+    use chessire::engine::options::{EngineOption, OptionType, OptionValue};
+    use chessire::settings::*;
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    const SPIN: EngineOption = EngineOption::new(
+        "Spin",
+        OptionType::Spin {
+            default: 5,
+            min: -10,
+            max: 10,
+        },
+    );
+    const CHECK: EngineOption = EngineOption::new("Check", OptionType::Check { default: true });
+    const COMBO: EngineOption = EngineOption::new(
+        "Combo",
+        OptionType::Combo {
+            default: "Normal",
+            vars: &["Solid", "Normal", "Risky"],
+        },
+    );
+    const BUTTON: EngineOption = EngineOption::new("Button", OptionType::Button);
+    const STRING: EngineOption = EngineOption::new("String", OptionType::String { default: "" });
+
+    /// runs the engine in uci mode with the given commands, and returns what it printed
+    fn uci(commands: &str) -> String {
+        let mut engine = Command::new(env!("CARGO_BIN_EXE_chessire"))
+            .args(["-r", "uci"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = engine.stdin.take().unwrap();
+        writeln!(stdin, "{}\nquit", commands).unwrap();
+        let output = engine.wait_with_output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn values_are_validated() {
+        assert_eq!(SPIN.parse(Some("-10")), Ok(OptionValue::Spin(-10)));
+        assert_eq!(SPIN.parse(Some("10")), Ok(OptionValue::Spin(10)));
+        assert!(SPIN.parse(Some("11")).is_err());
+        assert!(SPIN.parse(Some("five")).is_err());
+        assert!(SPIN.parse(None).is_err());
+
+        // the values of check and combo options are case insensitive
+        assert_eq!(CHECK.parse(Some("FALSE")), Ok(OptionValue::Check(false)));
+        assert_eq!(CHECK.parse(Some("true")), Ok(OptionValue::Check(true)));
+        assert!(CHECK.parse(Some("yes")).is_err());
+        assert_eq!(
+            COMBO.parse(Some("risky")),
+            Ok(OptionValue::Combo("Risky".to_string()))
+        );
+        assert!(COMBO.parse(Some("Reckless")).is_err());
+
+        assert_eq!(BUTTON.parse(None), Ok(OptionValue::Button));
+        assert_eq!(
+            STRING.parse(Some("a b c")),
+            Ok(OptionValue::String("a b c".to_string()))
+        );
+        assert!(STRING.parse(None).is_err());
+    }
+
+    #[test]
+    fn defaults_and_uci_format() {
+        assert_eq!(SPIN.default_value(), OptionValue::Spin(5));
+        assert_eq!(CHECK.default_value(), OptionValue::Check(true));
+        assert_eq!(
+            COMBO.default_value(),
+            OptionValue::Combo("Normal".to_string())
+        );
+        assert_eq!(
+            SPIN.to_string(),
+            "option name Spin type spin default 5 min -10 max 10"
+        );
+        assert_eq!(
+            CHECK.to_string(),
+            "option name Check type check default true"
+        );
+        assert_eq!(
+            COMBO.to_string(),
+            "option name Combo type combo default Normal var Solid var Normal var Risky"
+        );
+        assert_eq!(BUTTON.to_string(), "option name Button type button");
+        assert_eq!(
+            STRING.to_string(),
+            "option name String type string default <empty>"
+        );
+    }
+
+    #[test]
+    fn engine_options() {
+        let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+        // every option of the registry can be set to its default value
+        for option in engine.options() {
+            // there's no file to save to
+            if option.name == SAVE_EVAL_FILE {
+                continue;
+            }
+            assert_eq!(
+                engine.set_option(option.name, option.default_value()),
+                Ok(())
+            );
+        }
+
+        engine.set_option(MULTIPV, OptionValue::Spin(3)).unwrap();
+        assert_eq!(engine.settings.multipv, 3);
+        engine
+            .set_option(NULL_MOVE_PRUNING, OptionValue::Check(false))
+            .unwrap();
+        assert!(!engine.settings.null_move_pruning);
+
+        // the search is single threaded
+        let threads = engine
+            .options()
+            .into_iter()
+            .find(|option| option.name == THREADS)
+            .unwrap();
+        assert!(threads.parse(Some("2")).is_err());
+
+        // names or values the engine doesn't know are reported
+        assert!(engine
+            .set_option("Contempt", OptionValue::Spin(10))
+            .is_err());
+        assert!(engine.set_option(HASH, OptionValue::Check(true)).is_err());
+        assert!(engine.set_option(PONDER, OptionValue::Spin(1)).is_err());
+    }
+
+    #[test]
+    fn uci_lists_the_options() {
+        let output = uci("uci");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.last(), Some(&"uciok"));
+        let options: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|line| line.starts_with("option "))
+            .collect();
+        let expected: Vec<String> = ENGINE_OPTIONS.iter().map(|o| o.to_string()).collect();
+        assert_eq!(options, expected);
+        assert!(output.contains("option name Threads type spin default 1 min 1 max 1"));
+    }
+
+    #[test]
+    fn uci_setoption_errors() {
+        let output = uci("setoption name hash value 16\n\
+             setoption name Hash value 0\n\
+             setoption name Ponder value maybe\n\
+             setoption name Style value Risky\n\
+             setoption name Clear Hash\n\
+             isready");
+        let errors: Vec<&str> = output
+            .lines()
+            .filter(|line| line.starts_with("info string ERROR"))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "info string ERROR: value 0 for option Hash out of range [1, 1048576]",
+                "info string ERROR: invalid value maybe for option Ponder",
+                "info string ERROR: unknown option Style",
+            ]
+        );
+        assert!(output.ends_with("readyok\n"));
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test_ordering {
+    use chessire::constants::BLACK_QUEEN;
+    use chessire::ordering::MovePicker;
+    use chessire::test::{engine_from_fen, find_move, POSITION2, POSITION3, POSITION4, POSITION5};
+    use chessire::transposition::TtMove;
+    use chessire::{move_to_uci, BitBoardEngine, ChessEngine};
+    use chessire_utils::color::Color::White;
+    use chessire_utils::moves::Move;
+
+    // dxe5 wins the queen, dxc5 trades pawns and Qxg4 loses the queen for a pawn
+    const CAPTURES: &str = "4k3/8/1p6/2p1q2p/3P2p1/8/8/3Q3K w - - 0 1";
+
+    /// every move handed out by a picker at ply
+    fn picked(engine: &BitBoardEngine, tt_move: Option<Move>, ply: usize) -> Vec<String> {
+        let mut picker = MovePicker::new(engine, tt_move.map(TtMove::new), ply);
+        let mut moves = vec![];
+        while let Some(mov) = picker.next(engine) {
+            moves.push(move_to_uci(mov));
+        }
+        moves
+    }
+
+    #[test]
+    fn every_legal_move_once() {
+        // moves of another position can't be played here
+        let other = engine_from_fen(POSITION2);
+        let foreign = [find_move(&other, "e1g1"), find_move(&other, "d5e6")];
+
+        for fen in [POSITION2, POSITION3, POSITION4, POSITION5].iter() {
+            let mut engine = engine_from_fen(fen);
+            let legal = engine.get_legal_moves();
+            let mut expected: Vec<String> = legal.iter().map(move_to_uci).collect();
+            expected.sort();
+
+            let tt_moves = [None, Some(legal.get(0)), Some(foreign[0])];
+            for tt_move in tt_moves.iter().copied() {
+                engine.ordering.killers[3] = [Some(legal.get(legal.len() - 1)), Some(foreign[1])];
+                engine.ordering.killers[4] = [Some(foreign[0]), Some(legal.get(0))];
+                for ply in 2..=4 {
+                    let mut moves = picked(&engine, tt_move, ply);
+                    moves.sort();
+                    assert_eq!(moves, expected, "{}", fen);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tt_move_first() {
+        let engine = engine_from_fen(CAPTURES);
+        let quiet = find_move(&engine, "h1g1");
+        assert_eq!(picked(&engine, Some(quiet), 0)[0], "h1g1");
+        let bad_capture = find_move(&engine, "d1g4");
+        let moves = picked(&engine, Some(bad_capture), 0);
+        assert_eq!(moves[0], "d1g4");
+        assert_eq!(moves.iter().filter(|m| *m == "d1g4").count(), 1);
+    }
+
+    #[test]
+    fn captures_killers_quiets() {
+        // the black queen just moved, Kg1 will be its countermove
+        let mut engine = engine_from_fen("4k3/8/1p2q3/2p4p/3P2p1/8/8/3Q3K b - - 0 1");
+        let previous = find_move(&engine, "e6e5");
+        engine.make_move(previous).unwrap();
+
+        let killer = find_move(&engine, "d1d3");
+        let countermove = find_move(&engine, "h1g1");
+        let history = find_move(&engine, "d1f3");
+        engine.ordering.killers[1] = [Some(killer), None];
+        engine.ordering.countermoves[BLACK_QUEEN][previous.target.to_usize()] = Some(countermove);
+        engine.ordering.history[White as usize][history.source.to_usize()]
+            [history.target.to_usize()] = 500;
+
+        let moves = picked(&engine, None, 1);
+        // captures that don't lose material, most valuable victim first
+        assert_eq!(moves[..2], ["d4e5", "d4c5"]);
+        // the killers and the countermove
+        assert_eq!(moves[2..4], ["d1d3", "h1g1"]);
+        // the quiets by history
+        assert_eq!(moves[4], "d1f3");
+        // and the captures that lose material
+        assert_eq!(moves.last().unwrap(), "d1g4");
+        assert_eq!(moves.len(), engine.get_legal_moves().len());
+    }
+
+    #[test]
+    fn new_game_clears_the_tables() {
+        let mut engine = engine_from_fen(CAPTURES);
+        let mov = find_move(&engine, "d1d3");
+        engine.ordering.killers[5] = [Some(mov), Some(mov)];
+        engine.ordering.history[White as usize][3][19] = 1000;
+        engine.ordering.countermoves[0][0] = Some(mov);
+
+        engine.new_game();
+        assert!(engine
+            .ordering
+            .killers
+            .iter()
+            .flatten()
+            .all(|k| k.is_none()));
+        assert!(engine
+            .ordering
+            .history
+            .iter()
+            .flatten()
+            .flatten()
+            .all(|h| *h == 0));
+        assert!(engine
+            .ordering
+            .countermoves
+            .iter()
+            .flatten()
+            .all(|c| c.is_none()));
+    }
+}
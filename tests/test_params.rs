@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test_params {
+    use chessire::constants::{BLACK_KNIGHT, WHITE_KNIGHT};
+    use chessire::engine::options::OptionValue;
+    use chessire::evaluation::s;
+    use chessire::params::EvalParams;
+    use chessire::test::{engine_from_fen, play};
+    use chessire::ChessEngine;
+    use chessire_utils::board::Coord;
+
+    fn square(name: &str) -> usize {
+        name.parse::<Coord>().unwrap().to_usize()
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut params = EvalParams::default();
+        params.apply("KnightValue 400 300 KnightMg.e4 50").unwrap();
+        assert_ne!(params, EvalParams::default());
+        params.apply(&EvalParams::default().to_string()).unwrap();
+        assert_eq!(params, EvalParams::default());
+    }
+
+    #[test]
+    fn single_squares_and_values() {
+        let mut params = EvalParams::default();
+        params
+            .apply("# a comment\nKnightValue 400 300\nKnightMg.e4 50 # and another")
+            .unwrap();
+        assert_eq!(params.piece_value(WHITE_KNIGHT), s(400, 300));
+        assert_eq!(params.piece_square(WHITE_KNIGHT, square("e4")).mg, 50);
+        // black sees the table from the other side
+        assert_eq!(params.piece_square(BLACK_KNIGHT, square("e5")).mg, 50);
+        assert_eq!(params.psqt(BLACK_KNIGHT, square("e5")).mg, -450);
+    }
+
+    #[test]
+    fn invalid_parameters_change_nothing() {
+        let mut params = EvalParams::default();
+        assert!(params.apply("KnightValue 400 300 BishopValue 1").is_err());
+        assert!(params.apply("KnightValue 400 300 Dragon 1 2").is_err());
+        assert!(params.apply("KnightMg.i9 10").is_err());
+        assert!(params.apply("KnightValue 400 many").is_err());
+        assert_eq!(params, EvalParams::default());
+    }
+
+    #[test]
+    fn options_change_the_evaluation() {
+        let mut engine = engine_from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1");
+        let before = engine.evaluate();
+        engine
+            .set_option("Knight Value Eg", OptionValue::Spin(581))
+            .unwrap();
+        assert_eq!(engine.eval_params.piece_value(WHITE_KNIGHT).eg, 581);
+        assert!(engine.evaluate() > before + 250);
+
+        // moves are scored with the new parameters
+        play(&mut engine, &["b1c3"]);
+        assert_eq!(
+            engine.state.psqt,
+            engine.state.compute_psqt(&engine.eval_params, None)
+        );
+
+        engine
+            .set_option("Eval Params", OptionValue::String("KnightValue 0 0".into()))
+            .unwrap();
+        assert_eq!(engine.eval_params.piece_value(WHITE_KNIGHT), s(0, 0));
+        assert!(engine
+            .set_option("Eval File", OptionValue::String("/nonexistent/eval".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("chessire_test_params.txt");
+        let path = path.to_str().unwrap();
+        let mut params = EvalParams::default();
+        params.apply("QueenValue 1100 1000 PawnEg.a7 200").unwrap();
+        params.save(path).unwrap();
+        let loaded = EvalParams::load(path);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.unwrap(), params);
+    }
+}
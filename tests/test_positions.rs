@@ -1,5 +1,53 @@
 #[cfg(test)]
 mod test_positions {
+    use chessire::test::engine_from_fen;
+    use chessire::ChessEngine;
+
     #[test]
     fn start_position() {}
+
+    #[test]
+    fn unmake_move_restores_the_position() {
+        // castling, en passant and promotions all show up two plies deep
+        const FEN: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 3 12";
+        let mut engine = engine_from_fen(FEN);
+        let before = engine.state;
+
+        for mov in engine.get_moves(engine.state.side_to_move) {
+            engine.make_move(mov).unwrap();
+            for reply in engine.get_moves(engine.state.side_to_move) {
+                let after_move = engine.state;
+                engine.make_move(reply).unwrap();
+                engine.unmake_move();
+                assert_eq!(engine.state.current_position, after_move.current_position);
+                assert_eq!(engine.state.occupancies, after_move.occupancies);
+                assert_eq!(engine.state.hash, after_move.hash);
+            }
+            engine.unmake_move();
+            assert_eq!(engine.state.current_position, before.current_position);
+            assert_eq!(engine.state.occupancies, before.occupancies);
+            assert_eq!(engine.state.enpassant, before.enpassant);
+            assert_eq!(engine.state.halfmove_clock, before.halfmove_clock);
+            assert_eq!(engine.state.fullmove_clock, before.fullmove_clock);
+            assert_eq!(engine.state.hash, before.hash);
+        }
+        assert!(engine.history.is_empty());
+    }
+
+    #[test]
+    fn null_move_passes_the_turn() {
+        let mut engine =
+            engine_from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
+        let before = engine.state;
+
+        engine.make_null_move();
+        assert_eq!(engine.state.side_to_move, before.side_to_move.opponent());
+        assert_eq!(engine.state.enpassant, None);
+        assert_ne!(engine.state.hash, before.hash);
+        engine.unmake_move();
+        assert_eq!(engine.state.side_to_move, before.side_to_move);
+        assert_eq!(engine.state.enpassant, before.enpassant);
+        assert_eq!(engine.state.hash, before.hash);
+        assert!(engine.history.is_empty());
+    }
 }
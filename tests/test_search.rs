@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod test_search {
+    use chessire::engine::limits::SearchLimits;
+    use chessire::engine::options::OptionValue;
+    use chessire::engine::report::ReportedScore;
+    use chessire::search::{
+        reported_score, score_from_tt, score_to_tt, PvTable, INFINITY, MATE_IN_MAX_PLY, MATE_SCORE,
+    };
+    use chessire::settings::{
+        CHECK_EXTENSIONS, FUTILITY_PRUNING, LATE_MOVE_REDUCTIONS, NULL_MOVE_PRUNING, RAZORING,
+        REVERSE_FUTILITY_PRUNING,
+    };
+    use chessire::test::{engine_from_fen, POSITION2};
+    use chessire::transposition::{Bound, TranspositionTable};
+    use chessire::{move_to_uci, ChessEngine};
+
+    const BACK_RANK_MATE: &str = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    // Nd6+ forks the king and the queen
+    const FORK: &str = "4k3/8/8/1q6/4N3/8/P7/4K3 w - - 0 1";
+    // Kb6 takes the squares of the black king before Rh8 mates
+    const QUIET_MATE_IN_TWO: &str = "k7/8/2K5/8/8/8/8/7R w - - 0 1";
+    // mutual zugzwang, the pawn only promotes if black has to move
+    const ZUGZWANG_BLACK: &str = "2k5/8/1PK5/8/8/8/8/8 b - - 0 1";
+    const ZUGZWANG_WHITE: &str = "2k5/8/1PK5/8/8/8/8/8 w - - 0 1";
+
+    /// best move found in the position at the given depth
+    fn best_move(fen: &str, depth: usize) -> Option<String> {
+        let mut engine = engine_from_fen(fen);
+        engine.search_best_move(&SearchLimits::depth(depth));
+        engine.get_best_move().map(move_to_uci)
+    }
+
+    #[test]
+    fn takes_a_hanging_queen() {
+        let fen = "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1";
+        assert_eq!(best_move(fen, 4).as_deref(), Some("d2d5"));
+    }
+
+    #[test]
+    fn back_rank_mate_in_one() {
+        assert_eq!(best_move(BACK_RANK_MATE, 4).as_deref(), Some("a1a8"));
+    }
+
+    #[test]
+    fn nothing_to_play() {
+        // checkmated
+        assert_eq!(best_move("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", 4), None);
+        // stalemated
+        assert_eq!(best_move("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 4), None);
+    }
+
+    #[test]
+    fn stopped_searches_have_a_move() {
+        let legal: Vec<String> = engine_from_fen(POSITION2)
+            .get_legal_moves()
+            .iter()
+            .map(move_to_uci)
+            .collect();
+        for nodes in [1, 2, 100, 5000].iter() {
+            let mut engine = engine_from_fen(POSITION2);
+            let limits = SearchLimits {
+                nodes: Some(*nodes),
+                ..SearchLimits::new()
+            };
+            engine.search_best_move(&limits);
+            let best_move = engine.get_best_move().map(move_to_uci).unwrap();
+            assert!(legal.contains(&best_move), "{} nodes", nodes);
+        }
+
+        let best_move = best_move(POSITION2, 1).unwrap();
+        assert!(legal.contains(&best_move));
+
+        // or one of the moves it was restricted to
+        let mut engine = engine_from_fen(POSITION2);
+        let limits = SearchLimits {
+            nodes: Some(1),
+            searchmoves: vec![engine.get_legal_moves().get(7)],
+            ..SearchLimits::new()
+        };
+        engine.search_best_move(&limits);
+        assert_eq!(
+            engine.get_best_move().map(move_to_uci),
+            Some(legal[7].clone())
+        );
+    }
+
+    #[test]
+    fn aspiration_windows_find_the_full_window_move() {
+        let depth = 5;
+        let mut engine = engine_from_fen(FORK);
+        let mut pv = PvTable::new();
+        let score = engine.negamax(-INFINITY, INFINITY, depth, 0, &mut pv);
+        assert_eq!(move_to_uci(pv.line()[0]), "e4d6");
+
+        // guesses of the previous iteration far below and far above the score
+        for guess in [score - 500, score + 1500].iter() {
+            let mut engine = engine_from_fen(FORK);
+            engine.search.score = *guess;
+            let mut pv = PvTable::new();
+            let aspiration_score = engine.aspiration_search(depth, 1, &mut pv);
+            assert_eq!(move_to_uci(pv.line()[0]), "e4d6", "guess {}", guess);
+            assert!((aspiration_score - score).abs() < 50, "guess {}", guess);
+        }
+    }
+
+    #[test]
+    fn tactics_are_found_without_each_pruning_option() {
+        let options = [
+            None,
+            Some(NULL_MOVE_PRUNING),
+            Some(LATE_MOVE_REDUCTIONS),
+            Some(REVERSE_FUTILITY_PRUNING),
+            Some(FUTILITY_PRUNING),
+            Some(RAZORING),
+            Some(CHECK_EXTENSIONS),
+        ];
+        for option in options.iter().copied() {
+            // best move and score of a search with the option turned off
+            let search = |fen: &str, depth: usize| {
+                let mut engine = engine_from_fen(fen);
+                if let Some(name) = option {
+                    engine.set_option(name, OptionValue::Check(false)).unwrap();
+                }
+                engine.search_best_move(&SearchLimits::depth(depth));
+                let best_move = engine.get_best_move().map(move_to_uci).unwrap();
+                (best_move, engine.search.score)
+            };
+
+            let (best_move, score) = search(FORK, 6);
+            assert_eq!(best_move, "e4d6", "{:?}", option);
+            assert!(score > 300, "{:?}", option);
+
+            let (best_move, score) = search(QUIET_MATE_IN_TWO, 5);
+            assert_eq!(best_move, "c6b6", "{:?}", option);
+            assert!(score >= MATE_IN_MAX_PLY, "{:?}", option);
+
+            // whoever has to move loses the opposition
+            let (_, score) = search(ZUGZWANG_BLACK, 8);
+            assert!(score < -500, "{:?}", option);
+            let (_, score) = search(ZUGZWANG_WHITE, 8);
+            assert!(score < 500, "{:?}", option);
+        }
+    }
+
+    #[test]
+    fn no_null_move_in_pawn_endings() {
+        // passing would be the best move in a zugzwang, the null move pruning option must not
+        // change the search as long as no pawn promotes
+        let search = |null_move_pruning: bool| {
+            let mut engine = engine_from_fen(ZUGZWANG_WHITE);
+            engine
+                .set_option(NULL_MOVE_PRUNING, OptionValue::Check(null_move_pruning))
+                .unwrap();
+            engine.search_best_move(&SearchLimits::depth(8));
+            let pv: Vec<String> = engine.search.pv.iter().copied().map(move_to_uci).collect();
+            (pv, engine.search.score, engine.search.nodes)
+        };
+        assert_eq!(search(true), search(false));
+    }
+
+    #[test]
+    fn mates_are_reported_in_moves() {
+        let reported = |fen: &str, limits: SearchLimits| {
+            let mut engine = engine_from_fen(fen);
+            engine.search_best_move(&limits);
+            reported_score(engine.search.score)
+        };
+        assert_eq!(
+            reported(BACK_RANK_MATE, SearchLimits::depth(4)),
+            ReportedScore::Mate(1)
+        );
+        let limits = SearchLimits {
+            mate: Some(2),
+            ..SearchLimits::new()
+        };
+        assert_eq!(reported(QUIET_MATE_IN_TWO, limits), ReportedScore::Mate(2));
+        // Kb8 is forced before Rh8 mates, the side getting mated sees a negative mate
+        let fen = "k7/8/1K6/8/8/8/8/7R b - - 0 1";
+        assert_eq!(
+            reported(fen, SearchLimits::depth(4)),
+            ReportedScore::Mate(-1)
+        );
+        // anything else is in centipawns
+        assert!(matches!(
+            reported(FORK, SearchLimits::depth(4)),
+            ReportedScore::Centipawns(cp) if cp > 300
+        ));
+    }
+
+    #[test]
+    fn final_positions() {
+        let score = |fen: &str| {
+            engine_from_fen(fen).negamax(-INFINITY, INFINITY, 3, 0, &mut PvTable::new())
+        };
+        // stalemate is a draw
+        assert_eq!(score("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), 0);
+        // mated at the root
+        assert_eq!(score("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1"), -MATE_SCORE);
+    }
+
+    #[test]
+    fn mate_scores_in_the_transposition_table() {
+        // a mate 7 plies from the root found at ply 3 is stored as a mate 4 plies away
+        assert_eq!(score_to_tt(MATE_SCORE - 7, 3), MATE_SCORE - 4);
+        assert_eq!(score_to_tt(-MATE_SCORE + 7, 3), -MATE_SCORE + 4);
+        assert_eq!(score_to_tt(250, 3), 250);
+
+        // and is 2 plies further from the root when the position is reached 2 plies later
+        let tt = TranspositionTable::new(1).unwrap();
+        for (score, expected) in [
+            (MATE_SCORE - 7, MATE_SCORE - 9),
+            (-MATE_SCORE + 7, -MATE_SCORE + 9),
+            (-250, -250),
+        ]
+        .iter()
+        {
+            tt.store(0xABCD, 4, Bound::Exact, score_to_tt(*score, 3), None);
+            let entry = tt.probe(0xABCD).unwrap();
+            assert_eq!(score_from_tt(entry.score, 5), *expected);
+            assert_eq!(score_from_tt(entry.score, 3), *score);
+        }
+    }
+}
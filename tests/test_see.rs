@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod test_see {
+    use chessire::move_to_uci;
+    use chessire::test::{
+        engine_from_fen, find_move, POSITION1, POSITION2, POSITION3, POSITION4, POSITION5,
+    };
+
+    fn see_of(fen: &str, uci: &str) -> i32 {
+        let engine = engine_from_fen(fen);
+        engine.see(find_move(&engine, uci))
+    }
+
+    #[test]
+    fn exchanges() {
+        // undefended pawn
+        assert_eq!(
+            see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+        // the pawn is defended, and the rook behind the queen joins the exchange
+        assert_eq!(
+            see_of(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            -200
+        );
+        // the rook behind the first one recaptures through the x-ray
+        assert_eq!(see_of("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+        // en passant opens the file for the rook behind
+        assert_eq!(see_of("4r1k1/8/8/3pP3/8/8/8/4R1K1 w - d6 0 1", "e5d6"), 100);
+        // the king can't recapture a defended piece
+        assert_eq!(see_of("8/8/8/8/8/4k3/3q4/3RK3 w - - 0 1", "d1d2"), 1000);
+        // unless it is
+        assert_eq!(see_of("8/8/8/8/8/4k3/3q4/3R2K1 w - - 0 1", "d1d2"), 500);
+    }
+
+    #[test]
+    fn see_ge_agrees_with_see() {
+        for fen in [POSITION1, POSITION2, POSITION3, POSITION4, POSITION5] {
+            let engine = engine_from_fen(fen);
+            for mov in engine.get_captures() {
+                let see = engine.see(mov);
+                for threshold in [see - 1, see, see + 1] {
+                    assert_eq!(
+                        engine.see_ge(mov, threshold),
+                        see >= threshold,
+                        "{} see {} threshold {} in {}",
+                        move_to_uci(mov),
+                        see,
+                        threshold,
+                        fen
+                    );
+                }
+            }
+        }
+    }
+}
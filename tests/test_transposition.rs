@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod test_transposition {
+    use chessire::engine::options::OptionValue;
+    use chessire::settings::{HASH, MAX_HASH_MB};
+    use chessire::test::{engine_from_fen, find_move};
+    use chessire::transposition::{Bound, TranspositionTable, TtMove};
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+    use std::sync::Arc;
+
+    #[test]
+    fn entries_are_read_back() {
+        let tt = TranspositionTable::new(1).unwrap();
+        let start = BitBoardEngine::new_engine(ChessGame::new());
+        let quiet = find_move(&start, "e2e4");
+        let promoting = engine_from_fen("8/1P2k3/8/8/8/8/8/4K3 w - - 0 1");
+        let promotion = find_move(&promoting, "b7b8n");
+
+        let cases = [
+            (0x1234_5678_9ABC_DEF0, 7, Bound::Exact, 35, Some(quiet)),
+            (
+                0x0FED_CBA9_8765_4321,
+                0,
+                Bound::Lower,
+                -31_999,
+                Some(promotion),
+            ),
+            (0x5555_AAAA_5555_AAAA, 300, Bound::Upper, 0, None),
+        ];
+        for (key, depth, bound, score, mov) in cases.iter().copied() {
+            tt.store(key, depth, bound, score, mov);
+            let entry = tt.probe(key).unwrap();
+            // the depth is saturated to fit in a byte
+            assert_eq!(entry.depth, depth.min(255));
+            assert_eq!(entry.bound, bound);
+            assert_eq!(entry.score, score);
+            assert_eq!(entry.mov, mov.map(TtMove::new));
+        }
+        assert!(tt.probe(0x1111_2222_3333_4444).is_none());
+
+        // promotions to different pieces are different moves
+        let queen = find_move(&promoting, "b7b8q");
+        assert!(TtMove::new(promotion).matches(promotion));
+        assert!(!TtMove::new(promotion).matches(queen));
+    }
+
+    #[test]
+    fn clear_and_age() {
+        let tt = TranspositionTable::new(1).unwrap();
+        assert_eq!(tt.hashfull(), 0);
+        // enough positions to fill every entry of the sample
+        let keys: Vec<u64> = (1..200_000u64)
+            .map(|n| n.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        for key in &keys {
+            tt.store(*key, 1, Bound::Exact, 0, None);
+        }
+        assert!(tt.hashfull() > 990);
+
+        // older entries stay in the table, but aren't counted as used by this search
+        tt.age();
+        assert_eq!(tt.hashfull(), 0);
+        assert!(tt.probe(*keys.last().unwrap()).is_some());
+
+        tt.clear();
+        assert_eq!(tt.hashfull(), 0);
+        assert!(keys.iter().all(|key| tt.probe(*key).is_none()));
+    }
+
+    #[test]
+    fn same_position_replacement() {
+        let tt = TranspositionTable::new(1).unwrap();
+        let mov = find_move(&BitBoardEngine::new_engine(ChessGame::new()), "g1f3");
+        let key = 0xABCD_EF01_2345_6789;
+        tt.store(key, 10, Bound::Exact, 50, Some(mov));
+
+        // a much shallower bound doesn't replace a deeper result of the same search
+        tt.store(key, 2, Bound::Lower, 80, None);
+        assert_eq!(tt.probe(key).unwrap().depth, 10);
+
+        // an exact score does, and keeps the best move if it doesn't have one
+        tt.store(key, 2, Bound::Exact, 80, None);
+        let entry = tt.probe(key).unwrap();
+        assert_eq!((entry.depth, entry.score), (2, 80));
+        assert_eq!(entry.mov, Some(TtMove::new(mov)));
+
+        // results of older searches are always replaced
+        tt.store(key, 10, Bound::Exact, 50, None);
+        tt.age();
+        tt.store(key, 1, Bound::Upper, -20, None);
+        assert_eq!(tt.probe(key).unwrap().depth, 1);
+    }
+
+    #[test]
+    fn bucket_replacement() {
+        let tt = TranspositionTable::new(1).unwrap();
+        // the bucket is picked by the high bits of the key, small keys share the first one
+        for (key, depth) in [(1, 5), (2, 1), (3, 7), (4, 3)].iter().copied() {
+            tt.store(key, depth, Bound::Exact, 0, None);
+        }
+        // the bucket is full, the shallowest entry goes
+        tt.store(5, 2, Bound::Exact, 0, None);
+        assert!(tt.probe(2).is_none());
+        for key in [1, 3, 4, 5].iter().copied() {
+            assert!(tt.probe(key).is_some());
+        }
+
+        // entries of older searches go before shallower ones of the current search
+        tt.age();
+        tt.store(2, 1, Bound::Exact, 0, None);
+        assert!(tt.probe(5).is_none());
+        tt.store(6, 1, Bound::Exact, 0, None);
+        assert!(tt.probe(4).is_none());
+        for key in [1, 2, 3, 6].iter().copied() {
+            assert!(tt.probe(key).is_some());
+        }
+    }
+
+    #[test]
+    fn hash_option() {
+        let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+        engine.set_option(HASH, OptionValue::Spin(1)).unwrap();
+        assert_eq!(engine.settings.hash_mb, 1);
+        let tt = engine.tt.clone();
+
+        // sizes out of range are rejected before allocating anything, the current table is kept
+        let too_large = MAX_HASH_MB as i64 + 1;
+        let option = engine
+            .options()
+            .into_iter()
+            .find(|o| o.name == HASH)
+            .unwrap();
+        assert!(option.parse(Some(&too_large.to_string())).is_err());
+        for size in [0, too_large].iter().copied() {
+            assert!(engine.set_option(HASH, OptionValue::Spin(size)).is_err());
+            assert_eq!(engine.settings.hash_mb, 1);
+            assert!(Arc::ptr_eq(&engine.tt, &tt));
+        }
+
+        engine.set_option(HASH, OptionValue::Spin(2)).unwrap();
+        assert_eq!(engine.settings.hash_mb, 2);
+        assert!(!Arc::ptr_eq(&engine.tt, &tt));
+    }
+}
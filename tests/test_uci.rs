@@ -0,0 +1,375 @@
+#[cfg(test)]
+mod test_uci {
+    use chessire::interface::uci_position;
+    use chessire::test::{engine_from_fen, find_move, play};
+    use chessire::{BitBoardEngine, ChessEngine, ChessGame};
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// runs the engine in uci mode with the given commands, the last one being a go command,
+    /// and returns what it printed until the best move
+    fn search_output(commands: &str) -> String {
+        let mut engine = Engine::start();
+        engine.send(commands);
+        // any other command would stop the search, wait for it to end
+        let lines = engine.wait_for("bestmove", Duration::from_secs(30));
+        engine.quit();
+        lines.join("\n")
+    }
+
+    /// engine in uci mode driven one command at a time, its output is read on another thread
+    /// so the tests can wait for a line without blocking forever
+    struct Engine {
+        process: Child,
+        stdin: ChildStdin,
+        lines: Receiver<String>,
+    }
+
+    impl Engine {
+        fn start() -> Self {
+            let mut process = Command::new(env!("CARGO_BIN_EXE_chessire"))
+                .args(["-r", "uci"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let stdin = process.stdin.take().unwrap();
+            let stdout = BufReader::new(process.stdout.take().unwrap());
+            let (sender, lines) = mpsc::channel();
+            thread::spawn(move || {
+                for line in stdout.lines() {
+                    if sender.send(line.unwrap()).is_err() {
+                        break;
+                    }
+                }
+            });
+            Self {
+                process,
+                stdin,
+                lines,
+            }
+        }
+
+        fn send(&mut self, command: &str) {
+            writeln!(self.stdin, "{}", command).unwrap();
+        }
+
+        /// lines printed until the first one starting with prefix, included
+        fn wait_for(&self, prefix: &str, timeout: Duration) -> Vec<String> {
+            let deadline = Instant::now() + timeout;
+            let mut lines = vec![];
+            loop {
+                let left = deadline.saturating_duration_since(Instant::now());
+                match self.lines.recv_timeout(left) {
+                    Ok(line) => {
+                        let found = line.starts_with(prefix);
+                        lines.push(line);
+                        if found {
+                            return lines;
+                        }
+                    }
+                    Err(_) => panic!("no {} after {:?}, got {:?}", prefix, timeout, lines),
+                }
+            }
+        }
+
+        /// lines printed during the given time
+        fn read_for(&self, time: Duration) -> Vec<String> {
+            let deadline = Instant::now() + time;
+            let mut lines = vec![];
+            while let Ok(line) = self
+                .lines
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                lines.push(line);
+            }
+            lines
+        }
+
+        /// quits the engine and returns everything it printed before exiting
+        fn quit(mut self) -> Vec<String> {
+            self.send("quit");
+            self.process.wait().unwrap();
+            self.lines.iter().collect()
+        }
+    }
+
+    fn count(lines: &[String], prefix: &str) -> usize {
+        lines.iter().filter(|l| l.starts_with(prefix)).count()
+    }
+
+    #[test]
+    fn isready_and_stop_during_an_infinite_search() {
+        let mut engine = Engine::start();
+        engine.send("position startpos");
+        engine.send("go infinite");
+        engine.wait_for("info depth", Duration::from_secs(10));
+
+        // the search keeps going while the engine answers
+        engine.send("isready");
+        let lines = engine.wait_for("readyok", Duration::from_secs(5));
+        assert_eq!(count(&lines, "bestmove"), 0);
+        // and an infinite search never ends on its own
+        assert_eq!(
+            count(&engine.read_for(Duration::from_millis(500)), "bestmove"),
+            0
+        );
+
+        engine.send("stop");
+        engine.wait_for("bestmove", Duration::from_secs(5));
+        // the search is over, stop again does nothing
+        engine.send("stop");
+        engine.send("isready");
+        let lines = engine.wait_for("readyok", Duration::from_secs(5));
+        assert_eq!(count(&lines, "bestmove"), 0);
+        assert_eq!(count(&engine.quit(), "bestmove"), 0);
+    }
+
+    #[test]
+    fn ponderhit_starts_the_clock() {
+        let mut engine = Engine::start();
+        engine.send("position startpos moves e2e4");
+        // 2 seconds left allow at most 200 ms for the move
+        engine.send("go ponder wtime 2000 btime 2000");
+        engine.wait_for("info depth", Duration::from_secs(10));
+
+        // while pondering the clock doesn't run
+        let lines = engine.read_for(Duration::from_millis(1000));
+        assert_eq!(count(&lines, "bestmove"), 0);
+        engine.send("isready");
+        engine.wait_for("readyok", Duration::from_secs(5));
+
+        let ponderhit = Instant::now();
+        engine.send("ponderhit");
+        engine.wait_for("bestmove", Duration::from_secs(5));
+        assert!(ponderhit.elapsed() < Duration::from_millis(1000));
+        assert_eq!(count(&engine.quit(), "bestmove"), 0);
+    }
+
+    #[test]
+    fn stop_while_pondering() {
+        let mut engine = Engine::start();
+        engine.send("position startpos");
+        engine.send("go ponder wtime 2000 btime 2000");
+        engine.wait_for("info depth", Duration::from_secs(10));
+        // the opponent played another move, the ponder search still sends a best move
+        engine.send("stop");
+        engine.wait_for("bestmove", Duration::from_secs(5));
+        engine.send("position startpos moves d2d4");
+        engine.send("go depth 3");
+        let lines = engine.wait_for("bestmove", Duration::from_secs(10));
+        assert_eq!(count(&lines, "info depth"), 3);
+        assert_eq!(count(&engine.quit(), "bestmove"), 0);
+    }
+
+    #[test]
+    fn qnodes_only_in_debug_mode() {
+        let output = search_output("position startpos\ngo depth 3");
+        assert_eq!(
+            output
+                .lines()
+                .filter(|l| l.starts_with("info depth"))
+                .count(),
+            3
+        );
+        assert!(!output.contains("qnodes"));
+
+        let output = search_output(
+            "debug on\nsetoption name MultiPV value 2\nposition startpos\ngo depth 3",
+        );
+        assert_eq!(output.lines().filter(|l| l.contains("qnodes")).count(), 6);
+
+        let output = search_output("debug on\ndebug off\nposition startpos\ngo depth 3");
+        assert!(!output.contains("qnodes"));
+    }
+
+    /// engine set up by a position command, which starts with startpos or fen
+    fn position(command: &str) -> BitBoardEngine {
+        // start from another position, to see it's replaced
+        let mut engine = engine_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        uci_position(command.split_ascii_whitespace(), &mut engine);
+        engine
+    }
+
+    fn assert_position(engine: &BitBoardEngine, fen: &str) {
+        let expected = engine_from_fen(fen);
+        // the key covers the pieces, the side to move, castling and en passant
+        assert_eq!(engine.state.hash, expected.state.hash, "{}", fen);
+        assert_eq!(engine.state.halfmove_clock, expected.state.halfmove_clock);
+        assert_eq!(engine.state.fullmove_clock, expected.state.fullmove_clock);
+    }
+
+    #[test]
+    fn position_with_moves() {
+        assert_position(
+            &position("startpos moves e2e4 e7e5 g1f3"),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+        );
+        // castling on both sides
+        assert_position(
+            &position("fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1 e8c8"),
+            "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2",
+        );
+        // en passant
+        assert_position(
+            &position("startpos moves e2e4 a7a6 e4e5 d7d5 e5d6"),
+            "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3",
+        );
+        // promotions, in any case
+        assert_position(
+            &position("fen 8/1P2k3/8/8/8/8/6p1/4K3 w - - 0 1 moves b7b8N g2g1q"),
+            "1N6/4k3/8/8/8/8/8/4K1q1 w - - 0 2",
+        );
+    }
+
+    #[test]
+    fn fen_without_clocks() {
+        assert_position(
+            &position("fen 4k3/8/8/8/8/8/8/4K3 b - -"),
+            "4k3/8/8/8/8/8/8/4K3 b - - 0 1",
+        );
+        assert_position(
+            &position("fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - moves e2e4"),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        );
+    }
+
+    #[test]
+    fn moves_stop_at_the_first_bad_one() {
+        let after_e4 = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        // illegal, the pawn is gone
+        assert_position(&position("startpos moves e2e4 e2e4 d7d5"), after_e4);
+        // malformed
+        assert_position(&position("startpos moves e2e4 e7 d7d5"), after_e4);
+        assert_position(&position("startpos moves e2e4 z9z9q d7d5"), after_e4);
+        // the bishop is pinned
+        let pinned = "4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1";
+        assert_position(&position(&format!("fen {} moves e2d3", pinned)), pinned);
+
+        // an invalid FEN doesn't touch the position
+        let unchanged = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+        assert_position(&position("fen 4x3/8/8/8/8/8/8/4K3 w - - 0 1"), unchanged);
+        assert_position(&position("fen 4k3/8/8/8/8/8/8/4K3 x - - 0 1"), unchanged);
+        assert_position(&position("fen 4k3/8/8/8/8/8/8/4K3 w"), unchanged);
+    }
+
+    #[test]
+    fn bad_moves_are_reported() {
+        let mut engine = Engine::start();
+        engine.send("position startpos moves e2e4 e2e4 d7d5");
+        engine.send("position fen 4k3/8/8/8/8/8/8/4K3 w");
+        engine.send("isready");
+        let lines = engine.wait_for("readyok", Duration::from_secs(5));
+        assert_eq!(
+            lines[..2],
+            [
+                "info string ERROR: illegal or malformed move e2e4",
+                "info string ERROR: FEN should have 6 fields, found 2",
+            ]
+        );
+        engine.quit();
+    }
+
+    /// values of an info line by name, the pv runs until the end of the line
+    fn info_fields(line: &str) -> Vec<(String, String)> {
+        let mut tokens = line.split_ascii_whitespace();
+        assert_eq!(tokens.next(), Some("info"), "{}", line);
+        let mut fields = vec![];
+        while let Some(name) = tokens.next() {
+            let value = match name {
+                "score" => format!("{} {}", tokens.next().unwrap(), tokens.next().unwrap()),
+                "pv" => tokens.by_ref().collect::<Vec<_>>().join(" "),
+                _ => tokens.next().unwrap().to_string(),
+            };
+            fields.push((name.to_string(), value));
+        }
+        fields
+    }
+
+    #[test]
+    fn info_lines() {
+        let output = search_output("position startpos moves e2e4\ngo depth 5");
+        let lines: Vec<&str> = output
+            .lines()
+            .filter(|l| l.starts_with("info depth"))
+            .collect();
+        assert_eq!(lines.len(), 5);
+        for (depth, line) in lines.iter().enumerate() {
+            let fields = info_fields(line);
+            let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(
+                names,
+                [
+                    "depth", "seldepth", "multipv", "score", "nodes", "nps", "hashfull", "time",
+                    "pv"
+                ],
+                "{}",
+                line
+            );
+            let value = |name: &str| -> u64 {
+                let (_, value) = fields.iter().find(|(n, _)| n == name).unwrap();
+                value.parse().unwrap()
+            };
+            assert_eq!(value("depth"), depth as u64 + 1);
+            assert!(value("seldepth") >= 1);
+            assert_eq!(value("multipv"), 1);
+            assert!(value("nodes") > 0);
+            assert!(value("hashfull") <= 1000);
+            assert!(value("nps") <= value("nodes") * 1000 / value("time").max(1));
+
+            let (_, score) = &fields[3];
+            let (kind, cp) = score.split_once(' ').unwrap();
+            assert_eq!(kind, "cp", "{}", line);
+            cp.parse::<i32>().unwrap();
+            // the pv is a sequence of legal moves
+            let (_, pv) = &fields[8];
+            let moves: Vec<&str> = pv.split_ascii_whitespace().collect();
+            assert!(!moves.is_empty() && moves.len() <= depth + 1);
+            let mut engine = BitBoardEngine::new_engine(ChessGame::new());
+            play(&mut engine, &["e2e4"]);
+            play(&mut engine, &moves);
+        }
+        let best_move = output.lines().last().unwrap();
+        let (_, pv) = &info_fields(lines[4])[8];
+        let first = pv.split_ascii_whitespace().next().unwrap();
+        assert!(best_move.starts_with(&format!("bestmove {} ", first)));
+    }
+
+    #[test]
+    fn mate_scores() {
+        // mating and getting mated, in moves
+        let output = search_output("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3");
+        let last = output.lines().rev().find(|l| l.starts_with("info depth"));
+        assert!(last.unwrap().contains(" score mate 1 "), "{}", output);
+        assert!(output.ends_with("bestmove a1a8"), "{}", output);
+
+        let output = search_output("position fen k7/8/1K6/8/8/8/8/7R b - - 0 1\ngo depth 4");
+        let last = output.lines().rev().find(|l| l.starts_with("info depth"));
+        assert!(last.unwrap().contains(" score mate -1 "), "{}", output);
+    }
+
+    #[test]
+    fn current_move_after_a_while() {
+        // the move searched at the root is only sent after the first seconds
+        let output = search_output("position startpos\ngo movetime 4000");
+        let root = BitBoardEngine::new_engine(ChessGame::new());
+        let currmoves: Vec<&str> = output.lines().filter(|l| l.contains("currmove")).collect();
+        assert!(!currmoves.is_empty(), "{}", output);
+        for line in currmoves {
+            let fields = info_fields(line);
+            let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(names, ["depth", "currmove", "currmovenumber"], "{}", line);
+            fields[0].1.parse::<usize>().unwrap();
+            find_move(&root, &fields[1].1);
+            let number: usize = fields[2].1.parse().unwrap();
+            assert!(
+                (1..=root.get_legal_moves().len()).contains(&number),
+                "{}",
+                line
+            );
+        }
+    }
+}